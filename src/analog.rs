@@ -0,0 +1,13 @@
+/// Single analog input channel
+///
+/// `embedded_hal` 1.0 doesn't provide an ADC abstraction, implement this trait
+/// on top of the HAL specific ADC channel to use it with the analog UI elements.
+pub trait AnalogChannel {
+    /// Error reported when the sample can't be taken
+    type Error;
+
+    /// Takes a single sample of the channel
+    ///
+    /// The sample is a raw ADC reading, its range depends on the ADC resolution
+    fn read(&mut self) -> Result<u16, Self::Error>;
+}
@@ -0,0 +1,138 @@
+use crate::led::effects::{EffectType, LedEffect};
+use crate::led::Led;
+use crate::poll::{Poll, PollError};
+use crate::switch::{Switch, SwitchEvent};
+use crate::time::{Clock, Instant, Milliseconds};
+
+/// Default length of the [`IndicatorMode::PulseOnPress`] pulse
+const DEFAULT_PULSE_MS: u32 = 100;
+
+/// Interaction between the switch and the indicator LED of the [`LitButton`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndicatorMode {
+    /// LED is driven solely by the user code, see [`LitButton::led_mut`]
+    #[default]
+    Manual,
+    /// LED is lit while the switch is held
+    LightWhileHeld,
+    /// LED pulses on each press, see [`LitButton::set_pulse_duration`]
+    PulseOnPress,
+    /// LED toggles on each press, e.g. for an on/off button showing its state
+    ToggleOnPress,
+}
+
+/// Illuminated pushbutton made of a switch and its indicator LED
+///
+/// Polls both parts with a single instant and drives the LED from the switch
+/// according to the [`IndicatorMode`].
+pub struct LitButton<S: Switch<C>, L: Led<C>, C: Clock> {
+    switch: S,
+    led: L,
+    mode: IndicatorMode,
+    pulse_duration: Milliseconds<C::T>,
+}
+
+impl<S: Switch<C>, L: Led<C>, C: Clock> LitButton<S, L, C> {
+    /// Create new [`LitButton`] from the passed in `switch` and `led`
+    ///
+    /// The LED is driven manually until set otherwise, see [set_mode](#method.set_mode)
+    pub fn new(switch: S, led: L) -> Self {
+        Self {
+            switch,
+            led,
+            mode: IndicatorMode::Manual,
+            pulse_duration: Milliseconds::<C::T>::new(C::T::from(DEFAULT_PULSE_MS)),
+        }
+    }
+
+    /// Sets the interaction between the switch and the LED
+    ///
+    /// See [set_mode](#method.set_mode)
+    pub fn with_mode(mut self, mode: IndicatorMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Sets the interaction between the switch and the LED
+    ///
+    /// The LED is left as is by the mode change, the new mode takes over from
+    /// the next poll.
+    pub fn set_mode(&mut self, mode: IndicatorMode) {
+        self.mode = mode;
+    }
+
+    /// Returns the interaction between the switch and the LED
+    pub fn mode(&self) -> IndicatorMode {
+        self.mode
+    }
+
+    /// Sets the length of the [`IndicatorMode::PulseOnPress`] pulse
+    ///
+    /// See [set_pulse_duration](#method.set_pulse_duration)
+    pub fn with_pulse_duration(mut self, duration: Milliseconds<C::T>) -> Self {
+        self.set_pulse_duration(duration);
+        self
+    }
+
+    /// Sets the length of the [`IndicatorMode::PulseOnPress`] pulse, 100 ms by default
+    pub fn set_pulse_duration(&mut self, duration: Milliseconds<C::T>) {
+        self.pulse_duration = duration;
+    }
+
+    /// Returns a reference to the switch
+    pub fn switch(&self) -> &S {
+        &self.switch
+    }
+
+    /// Returns a reference to the indicator LED
+    pub fn led(&self) -> &L {
+        &self.led
+    }
+
+    /// Returns a mutable reference to the indicator LED, e.g. for setting
+    /// the effects in the [`IndicatorMode::Manual`]
+    pub fn led_mut(&mut self) -> &mut L {
+        &mut self.led
+    }
+
+    /// Releases the switch and the indicator LED
+    pub fn into_inner(self) -> (S, L) {
+        (self.switch, self.led)
+    }
+
+    /// Polls the switch, drives the LED according to the mode and polls it
+    ///
+    /// The LED is polled even if the switch couldn't be read.
+    ///
+    /// Returns the [`SwitchEvent`] that happened during this poll, if any
+    pub fn poll(&mut self, now: Instant<C>) -> Result<Option<SwitchEvent<C>>, S::Error> {
+        let event = self.switch.poll(now);
+        let is_pressed = matches!(event, Ok(Some(SwitchEvent::Pressed)));
+
+        match self.mode {
+            IndicatorMode::Manual => {}
+            IndicatorMode::LightWhileHeld => {
+                if self.led.is_on() != self.switch.is_pressed() {
+                    self.led.toggle();
+                }
+            }
+            IndicatorMode::PulseOnPress if is_pressed => {
+                self.led
+                    .set_effect(LedEffect::new(EffectType::Pulse(self.pulse_duration)));
+            }
+            IndicatorMode::ToggleOnPress if is_pressed => self.led.toggle(),
+            IndicatorMode::PulseOnPress | IndicatorMode::ToggleOnPress => {}
+        }
+
+        self.led.poll(now);
+        event
+    }
+}
+
+impl<S: Switch<C>, L: Led<C>, C: Clock> Poll<C> for LitButton<S, L, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now).map(|_| ()).map_err(|_| PollError)
+    }
+}
@@ -0,0 +1,141 @@
+use embedded_hal::digital::{OutputPin, PinState};
+
+use crate::led::effects::{self, Effect, LedEffect};
+use crate::led::{CycleBoundary, EffectAction, EffectEngine};
+use crate::poll::{Poll, PollError};
+use crate::time::{Clock, Instant, Milliseconds};
+
+/// Beeper driven by an [`OutputPin`] of `embedded_hal`
+///
+/// Meant for active buzzers (or passive ones behind a driver generating the tone),
+/// which sound while the pin is high. Beeps and beep patterns are driven by the same
+/// effect timeline as the LEDs, e.g. [`EffectType::Pulse`](effects::EffectType::Pulse)
+/// for a single beep or [`EffectType::Blink`](effects::EffectType::Blink) for an alarm.
+pub struct Buzzer<P: OutputPin, C: Clock, E: Effect<C> = LedEffect<C>> {
+    pin: P,
+    engine: EffectEngine<C, E>,
+    is_sounding: bool,
+}
+
+impl<P: OutputPin, C: Clock, E: Effect<C>> Buzzer<P, C, E> {
+    /// Create new [`Buzzer`] instance for the passed in `pin`
+    pub fn new(pin: P) -> Self {
+        Self {
+            pin,
+            engine: EffectEngine::new(),
+            is_sounding: false,
+        }
+    }
+
+    /// Sets the beep effect, replacing the current one
+    ///
+    /// The effect takes place on the next [poll](#method.poll) call
+    pub fn set_effect(&mut self, effect: E) {
+        self.engine.set_effect(effect);
+    }
+
+    /// Schedules the beep effect to replace the current one once the clock reaches `at`
+    ///
+    /// See [`PinLed::schedule_effect`](crate::led::PinLed::schedule_effect)
+    pub fn schedule_effect(&mut self, effect: E, at: Instant<C>) {
+        self.engine.schedule_effect(effect, at);
+    }
+
+    /// Cancels the scheduled beep effect and returns it
+    pub fn cancel_scheduled_effect(&mut self) -> Option<E> {
+        self.engine.cancel_scheduled()
+    }
+
+    /// Returns the current beep effect
+    ///
+    /// Returns [`None`] if the buzzer is idle
+    pub fn get_effect(&self) -> Option<&E> {
+        self.engine.effect()
+    }
+
+    /// Clears the current effect, silencing the buzzer on the next poll
+    pub fn clear_effect(&mut self) {
+        self.engine.take_effect();
+    }
+
+    /// Indicates that the buzzer is sounding
+    pub fn is_sounding(&self) -> bool {
+        self.is_sounding
+    }
+
+    /// Polls the buzzer, advancing the current effect and updating the pin
+    ///
+    /// This must be done in regular intervals, the same way as for the LEDs
+    ///
+    /// Returns the [`CycleBoundary`] if the effect entered a new cycle during this poll
+    pub fn poll(&mut self, now: Instant<C>) -> Result<Option<CycleBoundary>, P::Error> {
+        self.engine.arm_scheduled(now);
+        let is_sounding = match self.engine.poll(now) {
+            Some(EffectAction::SetOn) => true,
+            Some(EffectAction::SetOff) => false,
+            Some(EffectAction::SetDuty(level)) => level > u8::MAX / 2,
+            Some(EffectAction::Completed { is_on, .. }) => is_on,
+            None => self.engine.is_active() && self.is_sounding,
+        };
+
+        self.pin.set_state(PinState::from(is_sounding))?;
+        self.is_sounding = is_sounding;
+        Ok(self.engine.cycle_boundary())
+    }
+
+    /// Releases the buzzer pin
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+}
+
+impl<P: OutputPin, C: Clock> Buzzer<P, C> {
+    /// Sounds a single beep lasting for `duration`
+    pub fn beep(&mut self, duration: Milliseconds<C::T>) {
+        self.set_effect(LedEffect::new(effects::EffectType::Pulse(duration)));
+    }
+
+    /// Sounds a short confirmation double beep
+    ///
+    /// Two 100ms beeps separated by a 100ms pause
+    pub fn confirm(&mut self) {
+        let mut fx = LedEffect::new(effects::blink_period::<C>(C::T::from(200)));
+        fx.set_duration(Milliseconds::<C::T>::new(C::T::from(300)));
+        self.set_effect(fx);
+    }
+}
+
+impl<P: OutputPin, C: Clock, E: Effect<C>> Poll<C> for Buzzer<P, C, E> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now).map(|_| ()).map_err(|_| PollError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Buzzer;
+    use crate::mock::{MockClock, MockOutputPin, MockPin};
+
+    #[test]
+    fn confirm_sounds_two_100ms_beeps() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut buzzer = Buzzer::<MockOutputPin, MockClock>::new(pin.output());
+
+        buzzer.confirm();
+        // polled every 5 ms, sampled in the middle of each 50 ms step
+        let mut output = [false; 6];
+        for at in (0..300).step_by(5) {
+            clock.set(at);
+            buzzer.poll(clock.now()).unwrap();
+            if at % 50 == 25 {
+                output[at as usize / 50] = pin.is_high();
+            }
+        }
+        assert_eq!(output, [true, true, false, false, true, true]);
+
+        clock.set(305);
+        buzzer.poll(clock.now()).unwrap();
+        assert!(!pin.is_high());
+    }
+}
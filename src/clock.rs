@@ -0,0 +1,87 @@
+//! Software clock for the platforms without a HAL [`Clock`] implementation
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::time::{Clock, ClockError, Fraction, Instant};
+
+/// Millisecond clock driven by the user code
+///
+/// Drop-in [`Clock`] implementation for getting started without a HAL clock, e.g.
+/// advanced by [tick](#method.tick) from a SysTick interrupt handler. The clock
+/// is `Sync` and can be placed into a `static`, e.g.
+/// `static CLOCK: ManualClock = ManualClock::new();`.
+///
+/// The counter wraps around after ~49.7 days, the UI elements handle the rollover.
+/// It should be advanced from a single context only, as the updates are not atomic
+/// on all targets.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicU32,
+}
+
+impl ManualClock {
+    /// Create new [`ManualClock`] starting at zero
+    pub const fn new() -> Self {
+        Self {
+            now: AtomicU32::new(0),
+        }
+    }
+
+    /// Sets the current time in milliseconds
+    pub fn set(&self, ms: u32) {
+        self.now.store(ms, Ordering::Release);
+    }
+
+    /// Moves the current time forward by `ms` milliseconds
+    ///
+    /// Wraps around the same way as a hardware counter would
+    pub fn tick(&self, ms: u32) {
+        let now = self.now.load(Ordering::Acquire);
+        self.now.store(now.wrapping_add(ms), Ordering::Release);
+    }
+
+    /// Returns the current instant
+    pub fn now(&self) -> Instant<Self> {
+        Instant::new(self.now.load(Ordering::Acquire))
+    }
+}
+
+impl Clock for ManualClock {
+    type T = u32;
+
+    const SCALING_FACTOR: Fraction = Fraction::new(1, 1_000);
+
+    fn try_now(&self) -> Result<Instant<Self>, ClockError> {
+        Ok(self.now())
+    }
+}
+
+/// Frame counter clock for the fixed rate render loops
+///
+/// Stands in for the [`Clock`] of the LEDs driven by a frame counter instead of
+/// the time, see [`PinLed::poll_frame`](crate::led::PinLed::poll_frame). A single
+/// tick is a single frame and the [`Milliseconds`](crate::time::Milliseconds)
+/// of the effects count the frames, e.g. a `Milliseconds(30)` pulse lasts 30 frames
+/// regardless of the frame rate. The rates count the cycles per 1000 frames.
+///
+/// The clock has no time source of its own, the frames are passed to the polls.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FrameClock;
+
+impl FrameClock {
+    /// Returns the instant of the `frame`
+    pub fn frame(frame: u32) -> Instant<Self> {
+        Instant::new(frame)
+    }
+}
+
+impl Clock for FrameClock {
+    type T = u32;
+
+    // one frame per "millisecond", so that the effect durations are counted in frames
+    const SCALING_FACTOR: Fraction = Fraction::new(1, 1_000);
+
+    fn try_now(&self) -> Result<Instant<Self>, ClockError> {
+        Err(ClockError::NotRunning)
+    }
+}
@@ -0,0 +1,266 @@
+use embedded_hal::digital::InputPin;
+use num_traits::{Bounded, CheckedMul};
+
+use crate::poll::{Poll, PollError};
+use crate::time::{self, Clock, FixedPoint, Instant, Milliseconds};
+
+/// Quadrature state transition table
+///
+/// Indexed by the previous and the new `A << 1 | B` phase state as `prev << 2 | new`.
+/// Invalid transitions (both phases changing at once) are ignored.
+const TRANSITIONS: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
+/// Quadrature rotary encoder built on top of two [`InputPin`]s of `embedded_hal`
+///
+/// Decodes the A/B phases into a signed position. Turning the encoder so that
+/// the phase A leads the phase B increments the position, swap the pins to invert
+/// the direction.
+///
+/// Detented encoders usually produce four raw transitions per one logical click,
+/// see [with_steps_per_detent](#method.with_steps_per_detent).
+///
+/// Fast turns can be optionally accelerated, see [with_acceleration](#method.with_acceleration).
+pub struct Encoder<A: InputPin, B: InputPin<Error = A::Error>, C: Clock> {
+    pin_a: A,
+    pin_b: B,
+    state: Option<u8>,
+    steps_per_detent: u8,
+    sub_steps: i8,
+    position: i32,
+    delta: i32,
+    accelerated_delta: i32,
+    acceleration: Option<(Milliseconds<C::T>, u8)>,
+    last_step_at: Option<Instant<C>>,
+    last_step_interval: Option<Milliseconds<C::T>>,
+}
+
+impl<A: InputPin, B: InputPin<Error = A::Error>, C: Clock> Encoder<A, B, C> {
+    /// Create new [`Encoder`] instance for the passed in phase pins
+    ///
+    /// The encoder defaults to four raw transitions per logical step
+    pub fn new(pin_a: A, pin_b: B) -> Self {
+        Self {
+            pin_a,
+            pin_b,
+            state: None,
+            steps_per_detent: 4,
+            sub_steps: 0,
+            position: 0,
+            delta: 0,
+            accelerated_delta: 0,
+            acceleration: None,
+            last_step_at: None,
+            last_step_interval: None,
+        }
+    }
+
+    /// Sets the number of raw transitions counted as one logical step
+    ///
+    /// See [set_steps_per_detent](#method.set_steps_per_detent)
+    pub fn with_steps_per_detent(mut self, steps: u8) -> Self {
+        self.set_steps_per_detent(steps);
+        self
+    }
+
+    /// Sets the number of raw transitions counted as one logical step
+    ///
+    /// Use `1` to count every raw transition, `4` for the common encoders with
+    /// detents on each full quadrature cycle. The value is clamped to `1..=127`.
+    pub fn set_steps_per_detent(&mut self, steps: u8) {
+        self.steps_per_detent = steps.clamp(1, i8::MAX as u8);
+        self.sub_steps = 0;
+    }
+
+    /// Enables the acceleration of the fast turns
+    ///
+    /// See [set_acceleration](#method.set_acceleration)
+    pub fn with_acceleration(mut self, threshold: Milliseconds<C::T>, max_multiplier: u8) -> Self {
+        self.set_acceleration(Some((threshold, max_multiplier)));
+        self
+    }
+
+    /// Sets the acceleration of the fast turns as the `(threshold, max_multiplier)` pair
+    ///
+    /// Each logical step made within `threshold` of the previous one is multiplied
+    /// in the [accelerated delta](#method.take_accelerated_delta). The multiplier
+    /// grows linearly from one for the steps `threshold` apart up to `max_multiplier`
+    /// for the steps made at once, so slow turns keep the fine control while fast
+    /// turns skip through the values quickly.
+    ///
+    /// Passing [`None`] disables the acceleration, the accelerated delta then
+    /// matches the raw one.
+    pub fn set_acceleration(&mut self, acceleration: Option<(Milliseconds<C::T>, u8)>) {
+        self.acceleration = acceleration;
+    }
+
+    /// Returns the accumulated position of the encoder in logical steps
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// Returns the logical steps accumulated since the last call and clears them
+    pub fn take_delta(&mut self) -> i32 {
+        core::mem::take(&mut self.delta)
+    }
+
+    /// Returns the accelerated logical steps accumulated since the last call and clears them
+    ///
+    /// Accumulated independently of the raw [delta](#method.take_delta), see
+    /// [set_acceleration](#method.set_acceleration)
+    pub fn take_accelerated_delta(&mut self) -> i32 {
+        core::mem::take(&mut self.accelerated_delta)
+    }
+
+    /// Returns the duration between the last two logical steps
+    ///
+    /// Can be used to estimate the rotation velocity. Returns [`None`]
+    /// until at least two steps have been made.
+    pub fn last_step_interval(&self) -> Option<Milliseconds<C::T>> {
+        self.last_step_interval
+    }
+
+    /// Resets the position and the accumulated delta to zero
+    pub fn reset(&mut self) {
+        self.sub_steps = 0;
+        self.position = 0;
+        self.delta = 0;
+        self.accelerated_delta = 0;
+        self.last_step_at = None;
+        self.last_step_interval = None;
+    }
+
+    /// Polls the encoder phases, decoding the rotation since the last poll
+    ///
+    /// This must be done often enough not to miss any phase transition, i.e. faster
+    /// than the fastest expected rotation produces the raw transitions.
+    ///
+    /// Returns the number of logical steps made during this poll
+    pub fn poll(&mut self, now: Instant<C>) -> Result<i32, A::Error> {
+        let new_state = (u8::from(self.pin_a.is_high()?) << 1) | u8::from(self.pin_b.is_high()?);
+        let Some(prev_state) = self.state.replace(new_state) else {
+            return Ok(0);
+        };
+
+        self.sub_steps += TRANSITIONS[usize::from(prev_state << 2 | new_state)];
+
+        let steps_per_detent = self.steps_per_detent as i8;
+        let step = if self.sub_steps >= steps_per_detent {
+            1
+        } else if self.sub_steps <= -steps_per_detent {
+            -1
+        } else {
+            return Ok(0);
+        };
+
+        self.sub_steps -= step as i8 * steps_per_detent;
+        self.position = self.position.wrapping_add(step);
+        self.delta = self.delta.saturating_add(step);
+        self.last_step_interval = self
+            .last_step_at
+            .and_then(|at| time::duration_since(now, at));
+        self.last_step_at = Some(now);
+        self.accelerated_delta = self
+            .accelerated_delta
+            .saturating_add(step * i32::from(self.multiplier()));
+
+        Ok(step)
+    }
+
+    /// Returns the acceleration multiplier of the last logical step
+    fn multiplier(&self) -> u8 {
+        let (Some((threshold, max)), Some(interval)) = (self.acceleration, self.last_step_interval)
+        else {
+            return 1;
+        };
+        if interval >= threshold {
+            return 1;
+        }
+
+        // largest multiplier satisfying (m - 1) / (max - 1) <= (threshold - interval) / threshold
+        let threshold = threshold.integer();
+        let headroom = C::T::from(u32::from(max.saturating_sub(1)))
+            .checked_mul(&(threshold - interval.integer()))
+            .unwrap_or(C::T::max_value());
+        (2..=max)
+            .rev()
+            .find(|m| {
+                C::T::from(u32::from(m - 1))
+                    .checked_mul(&threshold)
+                    .is_some_and(|needed| needed <= headroom)
+            })
+            .unwrap_or(1)
+    }
+}
+
+impl<A: InputPin, B: InputPin<Error = A::Error>, C: Clock> Poll<C> for Encoder<A, B, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now).map(|_| ()).map_err(|_| PollError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Encoder;
+    use crate::mock::{MockClock, MockInputPin, MockPin};
+
+    type TestEncoder<'a> = Encoder<MockInputPin<'a>, MockInputPin<'a>, MockClock>;
+
+    /// Phase states `(A, B)` of one full cycle with the phase A leading
+    const FORWARD: [(bool, bool); 4] = [(true, false), (true, true), (false, true), (false, false)];
+
+    /// Phase states `(A, B)` of one full cycle with the phase B leading
+    const REVERSE: [(bool, bool); 4] = [(false, true), (true, true), (true, false), (false, false)];
+
+    /// Drives the phases through the `states`, returning the steps made
+    fn turn(encoder: &mut TestEncoder, pins: &(MockPin, MockPin), states: &[(bool, bool)]) -> i32 {
+        let clock = MockClock::new();
+        let mut steps = 0;
+        for (a, b) in states {
+            pins.0.set_high(*a);
+            pins.1.set_high(*b);
+            steps += encoder.poll(clock.now()).unwrap();
+            clock.advance(10);
+        }
+        steps
+    }
+
+    #[test]
+    fn full_cycles_make_one_detent() {
+        let pins = (MockPin::new(), MockPin::new());
+        let mut encoder = TestEncoder::new(pins.0.input(), pins.1.input());
+        turn(&mut encoder, &pins, &[(false, false)]);
+
+        assert_eq!(turn(&mut encoder, &pins, &FORWARD), 1);
+        assert_eq!(encoder.position(), 1);
+        assert_eq!(encoder.take_delta(), 1);
+
+        assert_eq!(turn(&mut encoder, &pins, &REVERSE), -1);
+        assert_eq!(encoder.position(), 0);
+        assert_eq!(encoder.take_delta(), -1);
+    }
+
+    #[test]
+    fn single_step_per_detent_counts_every_transition() {
+        let pins = (MockPin::new(), MockPin::new());
+        let mut encoder = TestEncoder::new(pins.0.input(), pins.1.input()).with_steps_per_detent(1);
+        turn(&mut encoder, &pins, &[(false, false)]);
+
+        assert_eq!(turn(&mut encoder, &pins, &FORWARD), 4);
+        assert_eq!(encoder.position(), 4);
+        assert_eq!(encoder.take_delta(), 4);
+        assert_eq!(encoder.take_delta(), 0);
+    }
+
+    #[test]
+    fn invalid_transitions_are_ignored() {
+        let pins = (MockPin::new(), MockPin::new());
+        let mut encoder = TestEncoder::new(pins.0.input(), pins.1.input()).with_steps_per_detent(1);
+        turn(&mut encoder, &pins, &[(false, false)]);
+
+        // both phases changing at once
+        let jumps = [(true, true), (false, false)];
+        assert_eq!(turn(&mut encoder, &pins, &jumps), 0);
+        assert_eq!(encoder.position(), 0);
+        assert_eq!(turn(&mut encoder, &pins, &FORWARD[..1]), 1);
+    }
+}
@@ -0,0 +1,280 @@
+use embedded_hal::digital::{InputPin, OutputPin};
+
+use crate::poll::{Poll, PollError};
+use crate::switch::{ReleaseKind, SwitchEvent};
+use crate::time::{elapsed_since, Clock, Instant, Milliseconds};
+
+/// Event of a single key reported by the [`Keypad`]
+#[derive(Copy, Clone, Debug)]
+pub struct KeyEvent<C: Clock> {
+    /// Row index of the key
+    pub row: usize,
+    /// Column index of the key
+    pub col: usize,
+    /// Event that happened on the key
+    pub event: SwitchEvent<C>,
+}
+
+// manual impls, the derives would require the clock itself to be `PartialEq`
+impl<C: Clock> PartialEq for KeyEvent<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.row == other.row && self.col == other.col && self.event == other.event
+    }
+}
+
+impl<C: Clock> Eq for KeyEvent<C> {}
+
+#[cfg(feature = "defmt")]
+impl<C: Clock> defmt::Format for KeyEvent<C>
+where
+    C::T: defmt::Format,
+{
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "KeyEvent {{ row: {}, col: {}, event: {} }}",
+            self.row,
+            self.col,
+            self.event
+        )
+    }
+}
+
+/// Debounced state tracking of a single key
+struct KeyState<C: Clock> {
+    is_pressed: bool,
+    has_changed: bool,
+    last_change_at: Instant<C>,
+    prev_state_lasted: Milliseconds<C::T>,
+    pending_since: Option<Instant<C>>,
+}
+
+impl<C: Clock> KeyState<C> {
+    fn new() -> Self {
+        Self {
+            is_pressed: false,
+            has_changed: false,
+            last_change_at: Instant::<C>::new(C::T::from(0)),
+            prev_state_lasted: Milliseconds::<C::T>::new(C::T::from(0)),
+            pending_since: None,
+        }
+    }
+
+    fn update(&mut self, new_state: bool, now: Instant<C>, debounce: Milliseconds<C::T>) {
+        self.has_changed = false;
+        if new_state == self.is_pressed {
+            self.pending_since = None;
+            return;
+        }
+
+        let changed_at = *self.pending_since.get_or_insert(now);
+        if elapsed_since(now, changed_at) < debounce {
+            return;
+        }
+
+        self.pending_since = None;
+        self.is_pressed = new_state;
+        self.has_changed = true;
+        self.prev_state_lasted = elapsed_since(changed_at, self.last_change_at);
+        self.last_change_at = changed_at;
+    }
+}
+
+/// Matrix keypad scanned through row output pins and column input pins
+///
+/// Rows are driven low one at a time while the idle rows are driven high.
+/// Columns are expected to be pulled up, so a pressed key reads low on its column
+/// while its row is being driven. Ghosting avoidance is not handled.
+///
+/// Each key is tracked and debounced in the same way as a
+/// [`PinSwitch`](crate::switch::PinSwitch).
+pub struct Keypad<const R: usize, const C: usize, ROW, COL, CLK>
+where
+    ROW: OutputPin,
+    COL: InputPin<Error = ROW::Error>,
+    CLK: Clock,
+{
+    rows: [ROW; R],
+    cols: [COL; C],
+    keys: [[KeyState<CLK>; C]; R],
+    debounce: Milliseconds<CLK::T>,
+    long_press_threshold: Option<Milliseconds<CLK::T>>,
+}
+
+impl<const R: usize, const C: usize, ROW, COL, CLK> Keypad<R, C, ROW, COL, CLK>
+where
+    ROW: OutputPin,
+    COL: InputPin<Error = ROW::Error>,
+    CLK: Clock,
+{
+    /// Create new [`Keypad`] instance for the passed in row and column pins
+    ///
+    /// All rows are driven to the idle (high) state
+    pub fn new(mut rows: [ROW; R], cols: [COL; C]) -> Result<Self, ROW::Error> {
+        for row in rows.iter_mut() {
+            row.set_high()?;
+        }
+
+        Ok(Self {
+            rows,
+            cols,
+            keys: core::array::from_fn(|_| core::array::from_fn(|_| KeyState::new())),
+            debounce: Milliseconds::<CLK::T>::new(CLK::T::from(0)),
+            long_press_threshold: None,
+        })
+    }
+
+    /// Sets the debounce window applied to every key
+    ///
+    /// See [`PinSwitch::set_debounce`](crate::switch::PinSwitch::set_debounce)
+    pub fn with_debounce(mut self, debounce: Milliseconds<CLK::T>) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Sets the threshold telling the taps and the long presses apart for every key
+    ///
+    /// See [`PinSwitch::set_long_press_threshold`](crate::switch::PinSwitch::set_long_press_threshold)
+    pub fn with_long_press_threshold(mut self, threshold: Milliseconds<CLK::T>) -> Self {
+        self.set_long_press_threshold(Some(threshold));
+        self
+    }
+
+    /// Sets the threshold telling the taps and the long presses apart for every key
+    ///
+    /// Passing [`None`] (default) reports all releases as the taps.
+    pub fn set_long_press_threshold(&mut self, threshold: Option<Milliseconds<CLK::T>>) {
+        self.long_press_threshold = threshold;
+    }
+
+    /// Indicates that the key at `row` and `col` is pressed
+    ///
+    /// Returns `false` for keys out of bounds
+    pub fn is_pressed(&self, row: usize, col: usize) -> bool {
+        self.key(row, col).is_some_and(|k| k.is_pressed)
+    }
+
+    /// Indicates that the key at `row` and `col` has changed its state during the last poll
+    ///
+    /// Returns `false` for keys out of bounds
+    pub fn has_changed(&self, row: usize, col: usize) -> bool {
+        self.key(row, col).is_some_and(|k| k.has_changed)
+    }
+
+    /// Returns `(row, col)` indices of all pressed keys
+    pub fn pressed_keys(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.indices().filter(|(r, c)| self.keys[*r][*c].is_pressed)
+    }
+
+    /// Returns the events of keys that have changed their state during the last poll
+    pub fn events(&self) -> impl Iterator<Item = KeyEvent<CLK>> + '_ {
+        self.indices().filter_map(|(row, col)| {
+            let key = &self.keys[row][col];
+            if !key.has_changed {
+                return None;
+            }
+
+            let event = if key.is_pressed {
+                SwitchEvent::Pressed
+            } else {
+                let is_long_press = self
+                    .long_press_threshold
+                    .is_some_and(|threshold| key.prev_state_lasted >= threshold);
+                SwitchEvent::Released {
+                    held_for: key.prev_state_lasted,
+                    kind: match is_long_press {
+                        true => ReleaseKind::LongPress,
+                        false => ReleaseKind::Tap,
+                    },
+                }
+            };
+            Some(KeyEvent { row, col, event })
+        })
+    }
+
+    /// Scans the whole matrix, updating the state of every key
+    ///
+    /// This must be done in regular intervals in order to make this abstraction
+    /// work properly, the same way as [`Switch::poll`](crate::switch::Switch::poll).
+    pub fn poll(&mut self, now: Instant<CLK>) -> Result<(), ROW::Error> {
+        for (row, keys) in self.rows.iter_mut().zip(self.keys.iter_mut()) {
+            row.set_low()?;
+            for (col, key) in self.cols.iter_mut().zip(keys.iter_mut()) {
+                let is_low = match col.is_low() {
+                    Ok(is_low) => is_low,
+                    Err(e) => {
+                        // leave the row idle so the next scan starts from a clean state
+                        let _ = row.set_high();
+                        return Err(e);
+                    }
+                };
+                key.update(is_low, now, self.debounce);
+            }
+            row.set_high()?;
+        }
+        Ok(())
+    }
+
+    /// Releases the row and column pins
+    pub fn release(self) -> ([ROW; R], [COL; C]) {
+        (self.rows, self.cols)
+    }
+
+    fn key(&self, row: usize, col: usize) -> Option<&KeyState<CLK>> {
+        self.keys.get(row).and_then(|keys| keys.get(col))
+    }
+
+    fn indices(&self) -> impl Iterator<Item = (usize, usize)> {
+        (0..R).flat_map(|row| (0..C).map(move |col| (row, col)))
+    }
+}
+
+impl<const R: usize, const C: usize, ROW, COL, CLK> Poll<CLK> for Keypad<R, C, ROW, COL, CLK>
+where
+    ROW: OutputPin,
+    COL: InputPin<Error = ROW::Error>,
+    CLK: Clock,
+{
+    fn poll_at(&mut self, now: Instant<CLK>) -> Result<(), PollError> {
+        self.poll(now).map_err(|_| PollError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyEvent, Keypad};
+    use crate::mock::{MockClock, MockInputPin, MockOutputPin, MockPin};
+    use crate::switch::{ReleaseKind, SwitchEvent};
+    use crate::time::Milliseconds;
+
+    type TestKeypad<'a> = Keypad<1, 1, MockOutputPin<'a>, MockInputPin<'a>, MockClock>;
+
+    fn release_kind(keypad: &TestKeypad) -> Option<ReleaseKind> {
+        keypad
+            .events()
+            .find_map(|KeyEvent { event, .. }| match event {
+                SwitchEvent::Released { kind, .. } => Some(kind),
+                _ => None,
+            })
+    }
+
+    #[test]
+    fn reports_long_presses_past_the_threshold() {
+        let clock = MockClock::new();
+        let (row, col) = (MockPin::new(), MockPin::new());
+        col.set_high(true);
+        let mut keypad = TestKeypad::new([row.output()], [col.input()])
+            .unwrap()
+            .with_long_press_threshold(Milliseconds(500));
+
+        for (held_for, kind) in [(100, ReleaseKind::Tap), (500, ReleaseKind::LongPress)] {
+            col.set_high(false);
+            keypad.poll(clock.now()).unwrap();
+            clock.advance(held_for);
+            col.set_high(true);
+            keypad.poll(clock.now()).unwrap();
+            assert_eq!(release_kind(&keypad), Some(kind));
+            clock.advance(100);
+        }
+    }
+}
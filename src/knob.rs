@@ -0,0 +1,242 @@
+use crate::analog::AnalogChannel;
+use crate::poll::{Poll, PollError};
+use crate::time::{elapsed_since, Clock, Instant, Milliseconds};
+
+/// Knob (potentiometer, slider, etc.) read through an [`AnalogChannel`]
+///
+/// Tracks the knob position with a deadband, the sample has to move more than
+/// the deadband away from the current value in order to register as a change, so the
+/// ADC noise doesn't cause jitter. The samples at the ends of the raw range always
+/// register, so the ends stay reachable regardless of the deadband.
+///
+/// The value is reported in the raw ADC counts by default, see
+/// [with_scale](#method.with_scale) to map it onto a user range.
+pub struct Knob<CH: AnalogChannel, C: Clock> {
+    channel: CH,
+    deadband: u16,
+    raw_max: u16,
+    scale: Option<(u16, u16)>,
+    raw: Option<u16>,
+    has_changed: bool,
+    last_change_at: Instant<C>,
+}
+
+impl<CH: AnalogChannel, C: Clock> Knob<CH, C> {
+    /// Create new [`Knob`] instance for the passed in `channel`
+    ///
+    /// The sample has to move more than `deadband` raw counts to register as a change
+    pub fn new(channel: CH, deadband: u16) -> Self {
+        Self {
+            channel,
+            deadband,
+            raw_max: u16::MAX,
+            scale: None,
+            raw: None,
+            has_changed: false,
+            last_change_at: Instant::<C>::new(C::T::from(0)),
+        }
+    }
+
+    /// Sets the user range the raw samples are mapped onto
+    ///
+    /// See [set_scale](#method.set_scale)
+    pub fn with_scale(mut self, raw_max: u16, min: u16, max: u16) -> Self {
+        self.set_scale(raw_max, min, max);
+        self
+    }
+
+    /// Sets the user range the raw samples are mapped onto
+    ///
+    /// The raw range `0..=raw_max` (e.g. `4095` for a 12-bit ADC) is mapped linearly
+    /// onto `min..=max`. `min` may be above `max` to invert the knob direction.
+    /// The samples above `raw_max` are clamped.
+    pub fn set_scale(&mut self, raw_max: u16, min: u16, max: u16) {
+        self.raw_max = raw_max;
+        self.scale = Some((min, max));
+    }
+
+    /// Sets the deadband in the raw counts
+    pub fn set_deadband(&mut self, deadband: u16) {
+        self.deadband = deadband;
+    }
+
+    /// Returns the deadband in the raw counts
+    pub fn deadband(&self) -> u16 {
+        self.deadband
+    }
+
+    /// Returns the current value of the knob, scaled onto the user range if set
+    ///
+    /// Returns [`None`] until the first poll
+    pub fn value(&self) -> Option<u16> {
+        self.raw.map(|raw| self.scaled(raw))
+    }
+
+    /// Returns the current raw value of the knob
+    ///
+    /// Returns [`None`] until the first poll
+    pub fn raw(&self) -> Option<u16> {
+        self.raw
+    }
+
+    /// Indicates that the knob value has changed
+    ///
+    /// The change is latched by [poll](#method.poll) and stays set until cleared by
+    /// [take_changed](#method.take_changed) or [clear_changed](#method.clear_changed).
+    pub fn has_changed(&self) -> bool {
+        self.has_changed
+    }
+
+    /// Clears the latched value change
+    pub fn clear_changed(&mut self) {
+        self.has_changed = false;
+    }
+
+    /// Returns the latched value change and clears it
+    pub fn take_changed(&mut self) -> bool {
+        core::mem::take(&mut self.has_changed)
+    }
+
+    /// Returns the duration for which the knob has been left untouched
+    ///
+    /// Saturates to zero if `now` precedes the last change
+    pub fn unchanged_for(&self, now: Instant<C>) -> Milliseconds<C::T> {
+        elapsed_since(now, self.last_change_at)
+    }
+
+    /// Resets the knob to the initial state, the next poll registers as a change
+    pub fn reset(&mut self) {
+        self.raw = None;
+        self.has_changed = false;
+        self.last_change_at = Instant::<C>::new(C::T::from(0));
+    }
+
+    /// Polls the knob, sampling the channel
+    ///
+    /// The first sample always registers as a change.
+    ///
+    /// Returns the new value if the knob has moved beyond the deadband during this poll
+    ///
+    /// Returns an error if the sample couldn't be taken. The knob state is left
+    /// untouched in such case, so the poll can be simply retried.
+    pub fn poll(&mut self, now: Instant<C>) -> Result<Option<u16>, CH::Error> {
+        let sample = self.channel.read()?.min(self.raw_max);
+        let has_moved = match self.raw {
+            Some(raw) if raw == sample => false,
+            Some(raw) => {
+                raw.abs_diff(sample) > self.deadband || sample == 0 || sample == self.raw_max
+            }
+            None => true,
+        };
+        if !has_moved {
+            return Ok(None);
+        }
+
+        self.raw = Some(sample);
+        self.has_changed = true;
+        self.last_change_at = now;
+        Ok(Some(self.scaled(sample)))
+    }
+
+    /// Maps the raw sample onto the user range
+    fn scaled(&self, raw: u16) -> u16 {
+        let Some((min, max)) = self.scale else {
+            return raw;
+        };
+        if self.raw_max == 0 {
+            return min;
+        }
+
+        let raw = raw.min(self.raw_max);
+        let span = u32::from(min.abs_diff(max));
+        let offset =
+            (u32::from(raw) * span + u32::from(self.raw_max) / 2) / u32::from(self.raw_max);
+        // offset never exceeds the span, which fits into u16
+        let offset = offset as u16;
+        if min <= max {
+            min + offset
+        } else {
+            min - offset
+        }
+    }
+}
+
+impl<CH: AnalogChannel, C: Clock> Poll<C> for Knob<CH, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now).map(|_| ()).map_err(|_| PollError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    use super::Knob;
+    use crate::analog::AnalogChannel;
+    use crate::mock::MockClock;
+
+    /// Channel reading the sample set by the test
+    struct StubChannel<'a>(&'a Cell<u16>);
+
+    impl AnalogChannel for StubChannel<'_> {
+        type Error = Infallible;
+
+        fn read(&mut self) -> Result<u16, Self::Error> {
+            Ok(self.0.get())
+        }
+    }
+
+    /// Samples the knob at `raw`, returning the registered value
+    fn sample(knob: &mut Knob<StubChannel, MockClock>, cell: &Cell<u16>, raw: u16) -> Option<u16> {
+        cell.set(raw);
+        knob.poll(MockClock::new().now()).unwrap()
+    }
+
+    #[test]
+    fn moves_within_the_deadband_do_not_register() {
+        let cell = Cell::new(0);
+        let mut knob = Knob::new(StubChannel(&cell), 10);
+
+        assert_eq!(sample(&mut knob, &cell, 1_000), Some(1_000));
+        assert_eq!(sample(&mut knob, &cell, 1_005), None);
+        assert_eq!(sample(&mut knob, &cell, 990), None);
+        assert_eq!(sample(&mut knob, &cell, 1_011), Some(1_011));
+        assert_eq!(knob.value(), Some(1_011));
+    }
+
+    #[test]
+    fn ends_of_the_range_always_register() {
+        let cell = Cell::new(0);
+        let mut knob = Knob::new(StubChannel(&cell), 100).with_scale(4_095, 0, 100);
+
+        assert_eq!(sample(&mut knob, &cell, 50), Some(1));
+        assert_eq!(sample(&mut knob, &cell, 0), Some(0));
+        assert_eq!(sample(&mut knob, &cell, 4_050), Some(99));
+        assert_eq!(sample(&mut knob, &cell, 4_095), Some(100));
+        // clamped to the raw maximum, which is already registered
+        assert_eq!(sample(&mut knob, &cell, 5_000), None);
+    }
+
+    #[test]
+    fn inverted_scale_maps_the_ends_the_other_way() {
+        let cell = Cell::new(0);
+        let mut knob = Knob::new(StubChannel(&cell), 0).with_scale(1_000, 100, 0);
+
+        assert_eq!(sample(&mut knob, &cell, 0), Some(100));
+        assert_eq!(sample(&mut knob, &cell, 250), Some(75));
+        assert_eq!(sample(&mut knob, &cell, 500), Some(50));
+        assert_eq!(sample(&mut knob, &cell, 1_000), Some(0));
+    }
+
+    #[test]
+    fn scaled_value_rounds_to_the_nearest_step() {
+        let cell = Cell::new(0);
+        let mut knob = Knob::new(StubChannel(&cell), 0).with_scale(4_095, 0, 10);
+
+        // a step spans 409.5 raw counts
+        assert_eq!(sample(&mut knob, &cell, 204), Some(0));
+        assert_eq!(sample(&mut knob, &cell, 205), Some(1));
+        assert_eq!(sample(&mut knob, &cell, 4_094), Some(10));
+    }
+}
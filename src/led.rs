@@ -1,20 +1,386 @@
 use embedded_hal::digital::{PinState, StatefulOutputPin};
-use embedded_time::duration::Milliseconds;
-use embedded_time::rate::Rate;
-use embedded_time::{Clock, Instant};
+use heapless::Vec;
+use num_traits::{Bounded, CheckedAdd};
 
-use self::effects::LedEffect;
+use self::effects::{Effect, EffectSnapshot, LedEffect};
+use crate::clock::FrameClock;
+use crate::poll::{Poll, PollError, PollGapMonitor};
+use crate::time::{self, Clock, FixedPoint, Instant, Milliseconds, TimeInt};
+
+mod bar_graph;
+mod chase;
+mod engine;
+pub mod gamma;
+mod group;
+pub mod presets;
+mod pwm;
+mod soft_pwm;
+mod traffic_light;
+
+pub use self::bar_graph::BarGraph;
+pub use self::chase::{ChaseDirection, ChaseEffect};
+pub use self::engine::{CycleBoundary, EffectAction, EffectEngine};
+pub use self::group::LedGroup;
+pub use self::pwm::PwmLed;
+pub use self::soft_pwm::SoftPwmLed;
+pub use self::traffic_light::{TrafficLight, TrafficLightState};
 
 pub mod effects {
-    use embedded_time::{duration::Milliseconds, rate::Hertz, Clock, Instant, TimeInt};
+    use crate::time::{self, Clock, FixedPoint, Hertz, Instant, Milliseconds, Rate, TimeInt};
+    use num_traits::{Bounded, CheckedMul};
+
+    /// LED state requested by an [`Effect`]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub enum DesiredPinState {
+        /// Turn the LED on
+        On,
+        /// Turn the LED off
+        Off,
+        /// Leave the LED as is
+        Keep,
+        /// Effect is over, the LED should be left in the passed state
+        Finished { is_on: bool },
+    }
+
+    /// Visual effect driving an LED
+    ///
+    /// Extension point for custom effects, the built-in effects are provided
+    /// by [`LedEffect`]. Only the [update](#tymethod.update) is required, the
+    /// effects not supporting the other operations can leave them as no-ops.
+    pub trait Effect<C: Clock> {
+        /// Advances the effect to `now`
+        ///
+        /// `is_on` is the current state of the LED. Returns the state the LED
+        /// should be driven to.
+        fn update(&mut self, now: Instant<C>, is_on: bool) -> DesiredPinState;
+
+        /// Returns the brightness level the effect requests at `now`
+        ///
+        /// Returns [`None`] for the effects driving the LED only on and off
+        fn level(&self, _now: Instant<C>) -> Option<u8> {
+            None
+        }
+
+        /// Returns the index of the current effect cycle
+        ///
+        /// Change of the index between the updates marks the cycle boundary,
+        /// see [`CycleBoundary`](super::CycleBoundary). Returns [`None`] if the effect
+        /// hasn't started yet or doesn't track its cycles.
+        fn cycle(&self) -> Option<u32> {
+            None
+        }
+
+        /// Sets the duration for which the effect should last
+        fn set_duration(&mut self, _dur: Milliseconds<C::T>) {}
+
+        /// Sets the phase offset applied once the effect starts
+        ///
+        /// See [`LedEffect::set_phase_offset`]
+        fn set_phase_offset(&mut self, _offset: Milliseconds<C::T>) {}
+
+        /// Returns the duration for which the effect is going to run from `now`
+        ///
+        /// Returns [`None`] for effects running infinitely
+        fn remaining(&self, _now: Instant<C>) -> Option<Milliseconds<C::T>> {
+            None
+        }
+
+        /// Pauses the effect at `now`
+        fn pause(&mut self, _now: Instant<C>) {}
+
+        /// Resumes the paused effect at `now`
+        fn resume(&mut self, _now: Instant<C>) {}
+
+        /// Sets the effect start point ahead of the first update
+        ///
+        /// Effects started at the same instant run in phase, see
+        /// [`Led::set_effect_at`](super::Led::set_effect_at)
+        fn set_started_at(&mut self, _at: Instant<C>) {}
+
+        /// Returns the kind of the effect
+        ///
+        /// Returns [`None`] for the effects not matching any of the built-in kinds
+        fn kind(&self) -> Option<EffectKind> {
+            None
+        }
+    }
+
+    /// Kind of the [`EffectType`] without its parameters
+    ///
+    /// Cheap to copy around, e.g. for deciding whether to change the running effect.
+    /// See [`Led::current_effect_kind`](super::Led::current_effect_kind)
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum EffectKind {
+        /// See [`EffectType::Pulse`]
+        Pulse,
+        /// See [`EffectType::PulseLevel`]
+        PulseLevel,
+        /// See [`EffectType::Blink`]
+        Blink,
+        /// See [`EffectType::BlinkPeriod`]
+        BlinkPeriod,
+        /// See [`EffectType::Fade`]
+        Fade,
+        /// See [`EffectType::Strobe`]
+        Strobe,
+        /// See [`EffectType::Ramp`]
+        Ramp,
+        /// See [`EffectType::Sos`]
+        Sos,
+        /// See [`EffectType::Solid`]
+        Solid,
+    }
 
     /// LED Effect type
-    #[derive(Copy, Clone, Debug)]
+    ///
+    /// With the `serde` feature the durations and rates are (de)serialized
+    /// as their plain integer values in milliseconds and Hz.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde::Serialize, serde::Deserialize),
+        serde(
+            from = "EffectTypeRepr<T>",
+            into = "EffectTypeRepr<T>",
+            bound(
+                serialize = "T: serde::Serialize",
+                deserialize = "T: serde::Deserialize<'de>"
+            )
+        )
+    )]
     pub enum EffectType<T: TimeInt = u32> {
         /// Single pulse. Effects does not repeat
         Pulse(Milliseconds<T>),
-        /// Blink at given Hz value
+        /// Single pulse at the brightness `level` for the `duration`, e.g. a dim
+        /// acknowledgement. Effect does not repeat, the LED is off once it's over.
+        ///
+        /// LEDs without dimming are on during the pulse at the levels above the
+        /// half level, the same as for the [`EffectType::Solid`].
+        PulseLevel {
+            duration: Milliseconds<T>,
+            level: u8,
+        },
+        /// Blink at given Hz value, i.e. the number of full on and off periods
+        /// per second, toggling the LED every half of the period
+        ///
+        /// Always starts in the on half regardless of the previous LED state, so the LEDs
+        /// started at the same instant blink in phase.
         Blink(Hertz<T>),
+        /// Blink with the given full on and off `period`, toggling the LED every half
+        /// of it. Allows the slow blinks not expressible in whole Hz.
+        ///
+        /// Always starts in the on half, the same as [`EffectType::Blink`].
+        BlinkPeriod(Milliseconds<T>),
+        /// One-way brightness ramp from `from` to `to` level over the `duration`.
+        /// Effect does not repeat, the LED holds the `to` level once it's over.
+        ///
+        /// Meant for smooth transitions between the LED states, LEDs without
+        /// dimming support switch the state once the ramp crosses the half level.
+        Fade {
+            from: u8,
+            to: u8,
+            duration: Milliseconds<T>,
+        },
+        /// Bursts of `count` rapid flashes followed by a dark `gap`, repeating.
+        /// Each flash and the pause between the flashes last for `flash`.
+        Strobe {
+            count: u8,
+            flash: Milliseconds<T>,
+            gap: Milliseconds<T>,
+        },
+        /// Repeating linear brightness ramp (sawtooth) from `min` to `max` level
+        /// over the `period`, snapping back to `min` at the start of each period.
+        /// The ramp runs from `max` down to `min` when `reverse` is set.
+        ///
+        /// LEDs without dimming switch the state once the ramp crosses the half level.
+        Ramp {
+            min: u8,
+            max: u8,
+            period: Milliseconds<T>,
+            reverse: bool,
+        },
+        /// SOS distress signal (`... --- ...`) in the Morse code timing, repeating.
+        /// Dots last for a single `unit`, dashes for three units. The signals are separated
+        /// by a single unit, the letters by three units and the repetitions by seven units.
+        Sos(Milliseconds<T>),
+        /// Constant brightness level held for the effect duration, or until cleared
+        /// if there is none. Meant as a transient overlay on top of the effect
+        /// priority stack, see [`PinLed::push_effect`](super::PinLed::push_effect).
+        ///
+        /// LEDs without dimming are on at the levels above the half level.
+        Solid(u8),
+    }
+
+    impl<T: TimeInt> EffectType<T> {
+        /// Returns the kind of the effect type
+        pub fn kind(&self) -> EffectKind {
+            match self {
+                EffectType::Pulse(_) => EffectKind::Pulse,
+                EffectType::PulseLevel { .. } => EffectKind::PulseLevel,
+                EffectType::Blink(_) => EffectKind::Blink,
+                EffectType::BlinkPeriod(_) => EffectKind::BlinkPeriod,
+                EffectType::Fade { .. } => EffectKind::Fade,
+                EffectType::Strobe { .. } => EffectKind::Strobe,
+                EffectType::Ramp { .. } => EffectKind::Ramp,
+                EffectType::Sos(_) => EffectKind::Sos,
+                EffectType::Solid(_) => EffectKind::Solid,
+            }
+        }
+    }
+
+    #[cfg(feature = "defmt")]
+    impl<T: TimeInt + defmt::Format> defmt::Format for EffectType<T> {
+        fn format(&self, f: defmt::Formatter) {
+            match self {
+                EffectType::Pulse(dur) => defmt::write!(f, "Pulse({}ms)", dur.integer()),
+                EffectType::PulseLevel { duration, level } => {
+                    defmt::write!(f, "PulseLevel({} for {}ms)", level, duration.integer())
+                }
+                EffectType::Blink(rate) => defmt::write!(f, "Blink({}Hz)", rate.integer()),
+                EffectType::BlinkPeriod(period) => {
+                    defmt::write!(f, "BlinkPeriod({}ms)", period.integer())
+                }
+                EffectType::Fade { from, to, duration } => {
+                    defmt::write!(f, "Fade({}->{} in {}ms)", from, to, duration.integer())
+                }
+                EffectType::Strobe { count, flash, gap } => defmt::write!(
+                    f,
+                    "Strobe({}x{}ms, gap {}ms)",
+                    count,
+                    flash.integer(),
+                    gap.integer()
+                ),
+                EffectType::Ramp {
+                    min,
+                    max,
+                    period,
+                    reverse,
+                } => defmt::write!(
+                    f,
+                    "Ramp({}->{} in {}ms, reverse: {})",
+                    min,
+                    max,
+                    period.integer(),
+                    reverse
+                ),
+                EffectType::Sos(unit) => defmt::write!(f, "Sos({}ms)", unit.integer()),
+                EffectType::Solid(level) => defmt::write!(f, "Solid({})", level),
+            }
+        }
+    }
+
+    /// Serialized form of the [`EffectType`] with the plain integers in place of
+    /// the time types
+    #[cfg(feature = "serde")]
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(rename = "EffectType")]
+    enum EffectTypeRepr<T> {
+        Pulse(T),
+        PulseLevel {
+            duration: T,
+            level: u8,
+        },
+        Blink(T),
+        BlinkPeriod(T),
+        Fade {
+            from: u8,
+            to: u8,
+            duration: T,
+        },
+        Strobe {
+            count: u8,
+            flash: T,
+            gap: T,
+        },
+        Ramp {
+            min: u8,
+            max: u8,
+            period: T,
+            reverse: bool,
+        },
+        Sos(T),
+        Solid(u8),
+    }
+
+    #[cfg(feature = "serde")]
+    impl<T: TimeInt> From<EffectType<T>> for EffectTypeRepr<T> {
+        fn from(fx_type: EffectType<T>) -> Self {
+            match fx_type {
+                EffectType::Pulse(dur) => Self::Pulse(dur.integer()),
+                EffectType::PulseLevel { duration, level } => Self::PulseLevel {
+                    duration: duration.integer(),
+                    level,
+                },
+                EffectType::Blink(rate) => Self::Blink(rate.integer()),
+                EffectType::BlinkPeriod(period) => Self::BlinkPeriod(period.integer()),
+                EffectType::Fade { from, to, duration } => Self::Fade {
+                    from,
+                    to,
+                    duration: duration.integer(),
+                },
+                EffectType::Strobe { count, flash, gap } => Self::Strobe {
+                    count,
+                    flash: flash.integer(),
+                    gap: gap.integer(),
+                },
+                EffectType::Ramp {
+                    min,
+                    max,
+                    period,
+                    reverse,
+                } => Self::Ramp {
+                    min,
+                    max,
+                    period: period.integer(),
+                    reverse,
+                },
+                EffectType::Sos(unit) => Self::Sos(unit.integer()),
+                EffectType::Solid(level) => Self::Solid(level),
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<T: TimeInt> From<EffectTypeRepr<T>> for EffectType<T> {
+        fn from(repr: EffectTypeRepr<T>) -> Self {
+            match repr {
+                EffectTypeRepr::Pulse(dur) => Self::Pulse(Milliseconds::<T>::new(dur)),
+                EffectTypeRepr::PulseLevel { duration, level } => Self::PulseLevel {
+                    duration: Milliseconds::<T>::new(duration),
+                    level,
+                },
+                EffectTypeRepr::Blink(rate) => Self::Blink(Hertz::<T>::new(rate)),
+                EffectTypeRepr::BlinkPeriod(period) => {
+                    Self::BlinkPeriod(Milliseconds::<T>::new(period))
+                }
+                EffectTypeRepr::Fade { from, to, duration } => Self::Fade {
+                    from,
+                    to,
+                    duration: Milliseconds::<T>::new(duration),
+                },
+                EffectTypeRepr::Strobe { count, flash, gap } => Self::Strobe {
+                    count,
+                    flash: Milliseconds::<T>::new(flash),
+                    gap: Milliseconds::<T>::new(gap),
+                },
+                EffectTypeRepr::Ramp {
+                    min,
+                    max,
+                    period,
+                    reverse,
+                } => Self::Ramp {
+                    min,
+                    max,
+                    period: Milliseconds::<T>::new(period),
+                    reverse,
+                },
+                EffectTypeRepr::Sos(unit) => Self::Sos(Milliseconds::<T>::new(unit)),
+                EffectTypeRepr::Solid(level) => Self::Solid(level),
+            }
+        }
     }
 
     /// LED Effect instance
@@ -24,22 +390,127 @@ pub mod effects {
     #[derive(Copy, Clone, Debug)]
     pub struct LedEffect<C: Clock> {
         current_cycle_started_at: Option<Instant<C>>,
+        cycle: u32,
         started_at: Option<Instant<C>>,
         duration: Option<Milliseconds<C::T>>,
+        paused_at: Option<Instant<C>>,
+        is_inverted: bool,
+        phase_offset: Milliseconds<C::T>,
         fx_type: EffectType<C::T>,
     }
 
+    #[cfg(feature = "defmt")]
+    impl<C: Clock> defmt::Format for LedEffect<C>
+    where
+        C::T: defmt::Format,
+    {
+        fn format(&self, f: defmt::Formatter) {
+            defmt::write!(
+                f,
+                "LedEffect {{ type: {}, started_at: {}, duration: {}, paused: {} }}",
+                self.fx_type,
+                self.started_at.map(|at| crate::time::ticks(at)),
+                self.duration.map(|dur| dur.integer()),
+                self.paused_at.is_some(),
+            )
+        }
+    }
+
     impl<C: Clock> LedEffect<C> {
         /// Create new LED Effect instance with the assigned effect type
         pub fn new(fx_type: EffectType<C::T>) -> Self {
             Self {
                 current_cycle_started_at: None,
+                cycle: 0,
                 fx_type,
                 duration: None,
+                paused_at: None,
+                is_inverted: false,
+                phase_offset: Milliseconds::<C::T>::new(C::T::from(0)),
                 started_at: None
             }
         }
 
+        /// Inverts the effect output
+        ///
+        /// See [set_inverted](#method.set_inverted)
+        pub fn with_inverted(mut self, is_inverted: bool) -> Self {
+            self.set_inverted(is_inverted);
+            self
+        }
+
+        /// Inverts the effect output, the on-time becomes the off-time and vice versa
+        ///
+        /// Applies to the brightness levels and to the state the LED is left in
+        /// once the effect completes as well, e.g. an inverted
+        /// [`EffectType::Pulse`] is a short dip of an otherwise lit LED.
+        pub fn set_inverted(&mut self, is_inverted: bool) {
+            self.is_inverted = is_inverted;
+        }
+
+        /// Indicates whether the effect output is inverted
+        pub fn is_inverted(&self) -> bool {
+            self.is_inverted
+        }
+
+        /// Sets the phase offset applied once the effect starts
+        ///
+        /// See [set_phase_offset](#method.set_phase_offset)
+        pub fn with_phase_offset(mut self, offset: Milliseconds<C::T>) -> Self {
+            self.set_phase_offset(offset);
+            self
+        }
+
+        /// Sets the phase offset applied once the effect starts
+        ///
+        /// The effect starts `offset` into its timeline, as if it has been started
+        /// `offset` before the first update. Identical blinks with evenly spaced offsets
+        /// on a row of LEDs make up a marquee, e.g. a loading ripple. The effect
+        /// duration counts from the shifted start as well.
+        ///
+        /// Has no effect on the effects which have already started, including
+        /// the ones started explicitly by [set_started_at](#method.set_started_at).
+        pub fn set_phase_offset(&mut self, offset: Milliseconds<C::T>) {
+            self.phase_offset = offset;
+        }
+
+        /// Returns the phase offset applied once the effect starts
+        pub fn phase_offset(&self) -> Milliseconds<C::T> {
+            self.phase_offset
+        }
+
+        /// Takes a snapshot of the effect at `now`
+        pub fn snapshot(&self, now: Instant<C>) -> EffectSnapshot<C::T> {
+            let zero = Milliseconds::<C::T>::new(C::T::from(0));
+            EffectSnapshot {
+                fx_type: self.fx_type,
+                duration_ms: self.duration.map(|dur| dur.integer()),
+                is_inverted: self.is_inverted,
+                elapsed_ms: self
+                    .started_at
+                    .map(|_| self.time_elapsed(now).unwrap_or(zero).integer()),
+                cycle_elapsed_ms: self.current_cycle_duration(now).unwrap_or(zero).integer(),
+                cycle: self.cycle,
+                is_paused: self.is_paused(),
+            }
+        }
+
+        /// Restores the effect from the `snapshot`, rebasing its timeline onto `now`
+        ///
+        /// The effect continues from the same phase it was captured at
+        pub fn from_snapshot(snapshot: EffectSnapshot<C::T>, now: Instant<C>) -> Self {
+            let mut fx = Self::new(snapshot.fx_type).with_inverted(snapshot.is_inverted);
+            fx.duration = snapshot.duration_ms.map(Milliseconds::<C::T>::new);
+            fx.paused_at = snapshot.is_paused.then_some(now);
+            if let Some(elapsed) = snapshot.elapsed_ms {
+                let cycle_elapsed = Milliseconds::<C::T>::new(snapshot.cycle_elapsed_ms);
+                fx.started_at = Some(time::rewind(now, Milliseconds::<C::T>::new(elapsed)));
+                fx.current_cycle_started_at = Some(time::rewind(now, cycle_elapsed));
+                fx.cycle = snapshot.cycle;
+            }
+            fx
+        }
+
         /// Indicates whether the effect has started
         pub fn has_started(&self) -> bool {
             self.started_at.is_some()
@@ -52,10 +523,13 @@ pub mod effects {
 
         /// Sets the effect start point
         ///
-        /// This should be only called by the poll functions of [`Led`] implementations
+        /// The effect starts on its first update by default. Setting the start point
+        /// ahead of it aligns the effect to a common epoch, see
+        /// [`Led::set_effect_at`](super::Led::set_effect_at).
         pub fn set_started_at(&mut self, now: Instant<C>) {
             self.started_at = Some(now);
             self.current_cycle_started_at = self.started_at;
+            self.cycle = 0;
         }
 
         /// Returns the effect type of this LED effect instance
@@ -63,6 +537,16 @@ pub mod effects {
             &self.fx_type
         }
 
+        /// Swaps the effect type while keeping the effect timeline
+        ///
+        /// Unlike setting a new effect, the effect start and the current cycle are
+        /// preserved, so e.g. the blink rate can be changed on the fly without
+        /// a visible hitch. If the current cycle is already longer than the cycle
+        /// of the new type, the cycle simply ends on the next update.
+        pub fn set_type(&mut self, fx_type: EffectType<C::T>) {
+            self.fx_type = fx_type;
+        }
+
         /// Returns the duration for which the effect should last
         pub fn get_duration(&self) -> Option<Milliseconds<C::T>> {
             self.duration
@@ -74,217 +558,1831 @@ pub mod effects {
         }
 
         /// Returns elapsed duration since the effect has started
+        ///
+        /// The time is frozen at the pause point while the effect is paused.
+        ///
+        /// The elapsed time survives the clock rollover, but can be measured only up to
+        /// half of the clock range (e.g. ~24.8 days for a `u32` millisecond clock).
+        /// Effect durations should stay below this limit. Returns [`None`] past it,
+        /// the same as when `now` precedes the effect start.
         pub fn time_elapsed(&self, now: Instant<C>) -> Option<Milliseconds<C::T>> {
-            if let Some(started_at) = &self.started_at {
-                return now
-                    .checked_duration_since(started_at)
-                    .map(|d| Milliseconds::<C::T>::try_from(d).unwrap());
-            }
-            None
+            let now = self.paused_at.unwrap_or(now);
+            time::duration_since(now, self.started_at?)
         }
 
         /// Returns the duration of current cycle
+        ///
+        /// The time is frozen at the pause point while the effect is paused.
+        /// Survives the clock rollover the same way as [time_elapsed](#method.time_elapsed).
         pub fn current_cycle_duration(&self, now: Instant<C>) -> Option<Milliseconds<C::T>> {
-            if let Some(started_at) = &self.current_cycle_started_at {
-                return now
-                    .checked_duration_since(started_at)
-                    .map(|d| Milliseconds::<C::T>::try_from(d).unwrap());
-            }
-            None
+            let now = self.paused_at.unwrap_or(now);
+            time::duration_since(now, self.current_cycle_started_at?)
         }
 
         /// Start new cycle at an timestamp
         pub fn start_new_cycle(&mut self, now: Instant<C>) {
             self.current_cycle_started_at = Some(now);
+            self.cycle = self.cycle.wrapping_add(1);
         }
-    }
 
-    #[inline]
-    pub fn pulse<C: Clock>(duration_ms: u16) -> EffectType<C::T> {
-        let v = C::T::from(duration_ms.into());
-        EffectType::Pulse::<C::T>(Milliseconds::<C::T>::new(v))
-    }
+        /// Returns the index of the current cycle, counted from zero
+        ///
+        /// Returns [`None`] if the effect hasn't started yet
+        pub fn cycle(&self) -> Option<u32> {
+            self.started_at.map(|_| self.cycle)
+        }
 
-    #[inline]
-    pub fn blink<C: Clock>(rate_hz: u8) -> EffectType<C::T> {
-        let v = C::T::from(rate_hz.into());
-        EffectType::Blink::<C::T>(Hertz::<C::T>::new(v))
-    }
-}
+        /// Indicates whether the effect is paused
+        pub fn is_paused(&self) -> bool {
+            self.paused_at.is_some()
+        }
 
-/// UI LED
-///
-/// This LED abstraction tracks it's status and provides
-/// an interface for setting visual effects such as blinking
-/// on the LED.
-///
-/// Implementors should own their resources
-/// TODO: implement ability to set the default state for the user
-pub trait Led<C: Clock> {
-    // Indicates whether the current state is on or off
-    fn is_on(&mut self) -> bool;
+        /// Pauses the effect at `now`
+        ///
+        /// Has no effect if the effect is already paused
+        pub fn pause(&mut self, now: Instant<C>) {
+            if self.paused_at.is_none() {
+                self.paused_at = Some(now);
+            }
+        }
 
-    /// Turns on the LED
-    ///
-    /// Has no effect if the LED is already turned on
-    fn turn_on(&mut self);
+        /// Resumes the paused effect at `now`
+        ///
+        /// The effect timeline is moved forward by the time spent paused,
+        /// so the effect continues from the exact phase it was paused at.
+        ///
+        /// Has no effect if the effect is not paused
+        pub fn resume(&mut self, now: Instant<C>) {
+            let Some(paused_at) = self.paused_at.take() else {
+                return;
+            };
+            self.started_at = self
+                .started_at
+                .map(|at| time::shift_forward(at, paused_at, now));
+            self.current_cycle_started_at = self
+                .current_cycle_started_at
+                .map(|at| time::shift_forward(at, paused_at, now));
+        }
 
-    /// Turns off the LED
-    ///
-    /// has no effect if the LED is already turned off
-    fn turn_off(&mut self);
+        /// Returns the duration for which the effect is going to run from `now`
+        ///
+        /// Accounts for both the effect duration and the natural length of the effect
+        /// type (e.g. the pulse length). Effects which haven't started yet report
+        /// their full length. Saturates to zero once the effect is over.
+        ///
+        /// Returns [`None`] for effects running infinitely
+        pub fn remaining(&self, now: Instant<C>) -> Option<Milliseconds<C::T>> {
+            let length = match (&self.fx_type, self.duration) {
+                (
+                    EffectType::Pulse(len)
+                    | EffectType::PulseLevel { duration: len, .. }
+                    | EffectType::Fade { duration: len, .. },
+                    Some(dur),
+                ) if !time::has_reached(dur, *len) => dur,
+                (
+                    EffectType::Pulse(len)
+                    | EffectType::PulseLevel { duration: len, .. }
+                    | EffectType::Fade { duration: len, .. },
+                    _,
+                ) => *len,
+                (_, dur) => dur?,
+            };
+            let elapsed = self
+                .time_elapsed(now)
+                .unwrap_or(Milliseconds::<C::T>::new(C::T::from(0)));
 
-    /// Toggles the led on/off
-    fn toggle(&mut self);
+            Some(time::remaining(elapsed, length))
+        }
 
-    /// Sets the effect on this LED instance
-    ///
-    /// By default effect will have infinite duration unless set otherwise by
-    /// [set_effect_duration](#method.set_effect_duration) call
-    ///
-    /// Setting the effect while another one is active will overwrite it on the next
-    /// [poll](#method.poll) call
-    fn set_effect(&mut self, effect: effects::LedEffect<C>);
+        /// Returns the brightness level of the [`EffectType::Fade`] effect at `now`
+        ///
+        /// Effects which haven't started yet report the starting level.
+        ///
+        /// Returns [`None`] for the other effect types
+        pub fn fade_level(&self, now: Instant<C>) -> Option<u8> {
+            let EffectType::Fade { from, to, duration } = self.fx_type else {
+                return None;
+            };
+            let elapsed = self
+                .time_elapsed(now)
+                .unwrap_or(Milliseconds::<C::T>::new(C::T::from(0)));
+            Some(interpolate(from, to, elapsed, duration))
+        }
 
-    /// Sets the current effect duration on this LED instance
-    ///
-    /// Can be used to prolong current effect duration
-    ///
-    /// Does nothing if no effect is currently in place
-    fn set_effect_duration(&mut self, dur: Milliseconds<C::T>);
+        /// Returns the brightness level of the [`EffectType::Ramp`] effect at `now`
+        ///
+        /// Effects which haven't started yet report the starting level.
+        ///
+        /// Returns [`None`] for the other effect types
+        pub fn ramp_level(&self, now: Instant<C>) -> Option<u8> {
+            let EffectType::Ramp {
+                min,
+                max,
+                period,
+                reverse,
+            } = self.fx_type
+            else {
+                return None;
+            };
+            let (from, to) = if reverse { (max, min) } else { (min, max) };
+            let position = self
+                .current_cycle_duration(now)
+                .unwrap_or(Milliseconds::<C::T>::new(C::T::from(0)));
+            Some(interpolate(from, to, position, period))
+        }
 
-    /// Returns the current LED effect
-    ///
-    /// Returns [`None`] if no effect is in place
-    fn get_effect(&self) -> Option<&LedEffect<C>>;
+        /// Returns the normalized position within the current cycle at `now`
+        ///
+        /// The position is a Q16 fixed point fraction, `0` at the cycle start and
+        /// approaching `1 << 16` at its end, e.g. to keep other outputs in lockstep
+        /// with a blink. The cycle is a single blink toggle, strobe burst with its gap,
+        /// SOS signal repetition, ramp period, or the whole pulse or fade.
+        ///
+        /// Returns [`None`] if the effect hasn't started yet, if its cycle has zero
+        /// length, once the pulse or fade is over, or for the [`EffectType::Solid`]
+        pub fn phase_q16(&self, now: Instant<C>) -> Option<u32> {
+            let position = self.current_cycle_duration(now)?.integer();
+            let (length, is_repeating) = match self.fx_type {
+                EffectType::Solid(_) => return None,
+                EffectType::Pulse(len)
+                | EffectType::PulseLevel { duration: len, .. }
+                | EffectType::Fade { duration: len, .. } => (len.integer(), false),
+                EffectType::Blink(_) | EffectType::BlinkPeriod(_) => {
+                    (self.blink_interval()?.integer(), true)
+                }
+                EffectType::Strobe { count, flash, gap } => {
+                    (strobe_lengths(count, flash, gap).1, true)
+                }
+                EffectType::Sos(unit) => (
+                    unit.integer()
+                        .checked_mul(&C::T::from(SOS_LENGTH))
+                        .unwrap_or(C::T::max_value()),
+                    true,
+                ),
+                EffectType::Ramp { period, .. } => (period.integer(), true),
+            };
 
-    /// Clears current the effect
-    ///
-    /// This should also revert the LED to the state it was in
-    /// before the effect took place
-    fn clear_effect(&mut self);
+            if length == C::T::from(0) {
+                return None;
+            }
+            if position >= length {
+                // repeating cycles end on the next update
+                return is_repeating.then_some(PHASE_ONE - 1);
+            }
 
-    /// Polls the LED, updating it's state tracking and hardware state
-    ///
-    /// This must be done in regular intervals in order to make this abstraction
-    /// work properly. There might be limits on what this abstraction can track based
-    /// on how small / large the intervals are.
-    fn poll(&mut self, now: Instant<C>);
-}
+            let one = C::T::from(PHASE_ONE);
+            let phase = match position.checked_mul(&one) {
+                Some(scaled) => scaled / length,
+                None => position / (length / one),
+            };
+            Some(time::saturating_u32(phase).min(PHASE_ONE - 1))
+        }
 
-pub struct PinLed<P: StatefulOutputPin, C: Clock> {
-    pin: P,
-    effect: Option<effects::LedEffect<C>>,
-    is_on: bool,
-}
+        /// Returns the interval between the toggles of the [`EffectType::Blink`]
+        /// and [`EffectType::BlinkPeriod`], half of their full period
+        ///
+        /// Returns [`None`] for the other effect types, or if the rate can't be
+        /// expressed in milliseconds
+        fn blink_interval(&self) -> Option<Milliseconds<C::T>> {
+            let period = match self.fx_type {
+                EffectType::Blink(rate) => rate.to_duration::<Milliseconds<C::T>>().ok()?,
+                EffectType::BlinkPeriod(period) => period,
+                _ => return None,
+            };
+            Some(Milliseconds::<C::T>::new(period.integer() / C::T::from(2)))
+        }
 
-impl<P: StatefulOutputPin, C: Clock> PinLed<P, C> {
-    pub fn new(pin: P) -> Self {
-        Self { pin, effect: None, is_on: false }
-    }
-}
+        /// Advances the cycles by all of the `interval`s the current cycle has run past
+        ///
+        /// The slow polls may miss several toggles of a fast blink, the cycle index
+        /// keeps the correct parity regardless. The new cycle start stays aligned to
+        /// the `interval`, so the timing doesn't drift with the poll jitter.
+        fn catch_up_cycles(
+            &mut self,
+            current_dur: Milliseconds<C::T>,
+            interval: Milliseconds<C::T>,
+        ) {
+            let (Some(started_at), interval) = (self.current_cycle_started_at, interval.integer())
+            else {
+                return;
+            };
+            if !time::is_past(current_dur, Milliseconds::<C::T>::new(interval))
+                || interval == C::T::from(0)
+            {
+                return;
+            }
 
-impl<P: StatefulOutputPin, C: Clock> Led<C> for PinLed<P, C> {
-    fn is_on(&mut self) -> bool {
-        self.is_on
-    }
+            // the cycle ends once the interval has been run past, not just reached
+            let cycles = (current_dur.integer() - C::T::from(1)) / interval;
+            let shift = cycles
+                .checked_mul(&interval)
+                .unwrap_or(current_dur.integer());
+            self.current_cycle_started_at =
+                Some(time::advance(started_at, Milliseconds::<C::T>::new(shift)));
+            // beyond u32 only the parity of the cycles is kept, it drives the blink phase
+            let parity = time::saturating_u32(cycles % C::T::from(2));
+            let cycles = match cycles > C::T::from(u32::MAX) {
+                true => (u32::MAX - 1) | parity,
+                false => time::saturating_u32(cycles),
+            };
+            self.cycle = self.cycle.wrapping_add(cycles);
+        }
 
-    fn turn_on(&mut self) {
-        self.is_on = true;
-    }
+        /// Tracks the burst and gap phases of the [`EffectType::Strobe`]
+        ///
+        /// Each burst is a single effect cycle. Returns whether the LED should be on at `now`
+        fn strobe_phase(
+            &mut self,
+            now: Instant<C>,
+            count: u8,
+            flash: Milliseconds<C::T>,
+            gap: Milliseconds<C::T>,
+        ) -> bool {
+            let (burst, cycle_len) = strobe_lengths(count, flash, gap);
+            let flash = flash.integer();
 
-    fn turn_off(&mut self) {
-        self.is_on = false;
-    }
+            let mut position = self
+                .current_cycle_duration(now)
+                .map_or(C::T::from(0), |dur| dur.integer());
+            if position >= cycle_len && cycle_len > C::T::from(0) {
+                self.start_new_cycle(now);
+                position = C::T::from(0);
+            }
 
-    fn toggle(&mut self) {
-        self.is_on = !self.is_on;
-    }
+            position < burst && (position / flash) % C::T::from(2) == C::T::from(0)
+        }
 
-    fn set_effect(&mut self, effect: effects::LedEffect<C>) {
-        self.effect = Some(effect);
-    }
+        /// Tracks the signals of the [`EffectType::Sos`]
+        ///
+        /// Each repetition of the whole signal is a single effect cycle. Returns whether
+        /// the LED should be on at `now`
+        fn sos_phase(&mut self, now: Instant<C>, unit: Milliseconds<C::T>) -> bool {
+            let unit = unit.integer();
+            if unit == C::T::from(0) {
+                return false;
+            }
+            let cycle_len = unit
+                .checked_mul(&C::T::from(SOS_LENGTH))
+                .unwrap_or(C::T::max_value());
 
-    fn set_effect_duration(&mut self, dur: Milliseconds<<C as Clock>::T>) {
-        if let Some(fx) = &mut self.effect {
-            fx.set_duration(dur)
-        }
-    }
+            let mut position = self
+                .current_cycle_duration(now)
+                .map_or(C::T::from(0), |dur| dur.integer());
+            if position >= cycle_len {
+                self.start_new_cycle(now);
+                position = C::T::from(0);
+            }
 
-    fn clear_effect(&mut self) {
-        self.effect = None;
-        self.turn_off();
-    }
+            // walk the signals in the units of time
+            let position = position / unit;
+            let mut signal_end = 0;
+            for (on, off) in SOS_SIGNALS {
+                signal_end += on;
+                if position < C::T::from(signal_end) {
+                    return true;
+                }
+                signal_end += off;
+                if position < C::T::from(signal_end) {
+                    return false;
+                }
+            }
+            false
+        }
 
-    fn poll(&mut self, now: Instant<C>) {
-        if let Some(fx) = &mut self.effect {
-            // LED has an effect, process effect
+        /// Advances the non-inverted effect, see [`Effect::update`]
+        fn update_state(&mut self, now: Instant<C>, is_on: bool) -> DesiredPinState {
+            // Paused effect keeps the LED as is
+            if self.is_paused() {
+                return DesiredPinState::Keep;
+            }
 
-            let elapsed = fx.time_elapsed(now);
+            // Effect is just starting, save current timestamp
+            let is_starting = !self.has_started();
+            if is_starting {
+                self.set_started_at(time::rewind(now, self.phase_offset));
+            }
 
             // check if effect should finish
-            if let Some(fx_dur) = fx.get_duration() {
-                if let Some(elapsed) = elapsed {
-                    if elapsed > fx_dur {
-                        // effect is over
-                        self.clear_effect();
-                        self.pin.set_low().unwrap();
-                        return;
-                    }
+            if let (Some(fx_dur), Some(elapsed)) = (self.duration, self.time_elapsed(now)) {
+                if time::is_past(elapsed, fx_dur) {
+                    return DesiredPinState::Finished { is_on: false };
                 }
             }
 
-            let mut clear_effect = false;
-
-            match fx.get_type() {
-                effects::EffectType::Pulse(dur) => {
-                    if let Some(current_dur) = fx.current_cycle_duration(now) {
-                        if current_dur > *dur {
-                            // effect is over
-                            clear_effect = true;
-                            self.pin.set_low().unwrap();
-                        } else if ! fx.has_started() {
-                            self.pin.set_high().unwrap();
-                        }
+            let current_dur = self
+                .current_cycle_duration(now)
+                .unwrap_or(Milliseconds::<C::T>::new(C::T::from(0)));
+            match self.fx_type {
+                EffectType::Pulse(dur) | EffectType::PulseLevel { duration: dur, .. }
+                    if time::is_past(current_dur, dur) =>
+                {
+                    DesiredPinState::Finished { is_on: false }
+                }
+                EffectType::Blink(_) | EffectType::BlinkPeriod(_) => {
+                    if let Some(interval) = self.blink_interval() {
+                        self.catch_up_cycles(current_dur, interval);
+                    }
+                    // phase follows the cycle index, not the previous output state
+                    match self.cycle % 2 == 0 {
+                        true if !is_on || is_starting => DesiredPinState::On,
+                        false if is_on || is_starting => DesiredPinState::Off,
+                        _ => DesiredPinState::Keep,
+                    }
+                }
+                EffectType::Pulse(_) if is_starting || !is_on => DesiredPinState::On,
+                EffectType::Pulse(_) => DesiredPinState::Keep,
+                EffectType::PulseLevel { level, .. } => match level > u8::MAX / 2 {
+                    true if !is_on || is_starting => DesiredPinState::On,
+                    false if is_on || is_starting => DesiredPinState::Off,
+                    _ => DesiredPinState::Keep,
+                },
+                EffectType::Fade { to, duration, .. } if time::is_past(current_dur, duration) => {
+                    DesiredPinState::Finished { is_on: to > 0 }
+                }
+                EffectType::Fade { to, .. } => {
+                    // LEDs without dimming follow the ramp crossing the half level
+                    match self.fade_level(now).unwrap_or(to) > u8::MAX / 2 {
+                        true => DesiredPinState::On,
+                        false => DesiredPinState::Off,
+                    }
+                }
+                EffectType::Strobe { count, flash, gap } => {
+                    match self.strobe_phase(now, count, flash, gap) {
+                        true if !is_on || is_starting => DesiredPinState::On,
+                        false if is_on => DesiredPinState::Off,
+                        _ => DesiredPinState::Keep,
                     }
                 }
-                effects::EffectType::Blink(rate) => {
-                    if let Some(current_dur) = fx.current_cycle_duration(now) {
-                        if current_dur > rate.to_duration::<Milliseconds<C::T>>().unwrap() {
-                            // toggle the led on/off on each state change
-                            if self.pin.is_set_low().unwrap() {
-                                self.pin.set_high().unwrap();
-                            } else {
-                                self.pin.set_low().unwrap();
-                            }
-                            fx.start_new_cycle(now);
-                        }
+                EffectType::Sos(unit) => match self.sos_phase(now, unit) {
+                    true if !is_on || is_starting => DesiredPinState::On,
+                    false if is_on => DesiredPinState::Off,
+                    _ => DesiredPinState::Keep,
+                },
+                EffectType::Solid(level) => match level > u8::MAX / 2 {
+                    true if !is_on || is_starting => DesiredPinState::On,
+                    false if is_on || is_starting => DesiredPinState::Off,
+                    _ => DesiredPinState::Keep,
+                },
+                EffectType::Ramp { min, period, .. } => {
+                    // snap back at the end of each period
+                    if time::has_reached(current_dur, period) && period.integer() > C::T::from(0) {
+                        self.start_new_cycle(now);
+                    }
+                    // LEDs without dimming follow the ramp crossing the half level
+                    match self.ramp_level(now).unwrap_or(min) > u8::MAX / 2 {
+                        true => DesiredPinState::On,
+                        false => DesiredPinState::Off,
                     }
                 }
             }
+        }
+    }
 
-            if clear_effect {
-                self.clear_effect();
-                return;
+    /// Snapshot of the [`LedEffect`]
+    ///
+    /// Captures the effect timeline as the offsets from the moment the snapshot was
+    /// taken, so it can be restored on a clock with a different epoch, e.g. after a reset.
+    /// See [`LedEffect::snapshot`] and [`LedEffect::from_snapshot`].
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EffectSnapshot<T: TimeInt = u32> {
+        /// Effect type
+        pub fx_type: EffectType<T>,
+        /// Effect duration in milliseconds, [`None`] for the effects running infinitely
+        pub duration_ms: Option<T>,
+        /// Effect output is inverted, see [`LedEffect::set_inverted`]
+        pub is_inverted: bool,
+        /// Milliseconds elapsed since the effect start, [`None`] if it hasn't started
+        pub elapsed_ms: Option<T>,
+        /// Milliseconds elapsed since the current cycle start
+        pub cycle_elapsed_ms: T,
+        /// Index of the current cycle
+        pub cycle: u32,
+        /// Effect is paused
+        pub is_paused: bool,
+    }
+
+    impl<C: Clock> Effect<C> for LedEffect<C> {
+        fn update(&mut self, now: Instant<C>, is_on: bool) -> DesiredPinState {
+            // the effect itself runs on the non-inverted LED state
+            let state = self.update_state(now, is_on != self.is_inverted);
+            if !self.is_inverted {
+                return state;
             }
 
-            // Effect is just starting, save current timestamp
-            if ! fx.has_started() {
-                fx.set_started_at(now);
+            match state {
+                DesiredPinState::On => DesiredPinState::Off,
+                DesiredPinState::Off => DesiredPinState::On,
+                DesiredPinState::Keep => DesiredPinState::Keep,
+                DesiredPinState::Finished { is_on } => DesiredPinState::Finished { is_on: !is_on },
             }
-        } else {
-            // No effect on led, proceed as normal
-            let state = self.is_on;
-            self.pin
-                .set_state(match state {
-                    false => PinState::High,
-                    true => PinState::Low,
-                })
-                .unwrap()
         }
-    }
 
-    fn get_effect(&self) -> Option<&LedEffect<C>> {
-        self.effect.as_ref()
+        fn level(&self, now: Instant<C>) -> Option<u8> {
+            let level = match self.fx_type {
+                EffectType::Solid(level) => level,
+                // the level drops back to off once the pulse is over
+                EffectType::PulseLevel { level, .. } => level,
+                _ => self.fade_level(now).or_else(|| self.ramp_level(now))?,
+            };
+            match self.is_inverted {
+                true => Some(u8::MAX - level),
+                false => Some(level),
+            }
+        }
+
+        fn cycle(&self) -> Option<u32> {
+            LedEffect::cycle(self)
+        }
+
+        fn set_duration(&mut self, dur: Milliseconds<C::T>) {
+            LedEffect::set_duration(self, dur)
+        }
+
+        fn set_phase_offset(&mut self, offset: Milliseconds<C::T>) {
+            LedEffect::set_phase_offset(self, offset)
+        }
+
+        fn remaining(&self, now: Instant<C>) -> Option<Milliseconds<C::T>> {
+            LedEffect::remaining(self, now)
+        }
+
+        fn pause(&mut self, now: Instant<C>) {
+            LedEffect::pause(self, now)
+        }
+
+        fn resume(&mut self, now: Instant<C>) {
+            LedEffect::resume(self, now)
+        }
+
+        fn set_started_at(&mut self, at: Instant<C>) {
+            LedEffect::set_started_at(self, at)
+        }
+
+        fn kind(&self) -> Option<EffectKind> {
+            Some(self.fx_type.kind())
+        }
+    }
+
+    /// On and off units of the SOS signals, the last off period separates the repetitions
+    const SOS_SIGNALS: [(u32, u32); 9] = [
+        (1, 1),
+        (1, 1),
+        (1, 3),
+        (3, 1),
+        (3, 1),
+        (3, 3),
+        (1, 1),
+        (1, 1),
+        (1, 7),
+    ];
+
+    /// Length of a single SOS repetition in units
+    const SOS_LENGTH: u32 = 34;
+
+    /// Fractional bits of the effect phase, see [`LedEffect::phase_q16`]
+    const PHASE_BITS: u32 = 16;
+
+    /// Effect phase of a whole cycle
+    const PHASE_ONE: u32 = 1 << PHASE_BITS;
+
+    /// Linearly interpolates the brightness level between `from` and `to`
+    ///
+    /// Saturates to `to` once `elapsed` reaches the `duration`
+    fn interpolate<T: TimeInt>(
+        from: u8,
+        to: u8,
+        elapsed: Milliseconds<T>,
+        duration: Milliseconds<T>,
+    ) -> u8 {
+        if from == to || time::has_reached(elapsed, duration) {
+            return to;
+        }
+
+        let span = T::from(u32::from(from.abs_diff(to)));
+        let (elapsed, duration) = (elapsed.integer(), duration.integer());
+        let step = match span.checked_mul(&elapsed) {
+            Some(scaled) => scaled / duration,
+            None => elapsed / (duration / span),
+        };
+        let step = level_from_int(step);
+
+        if to > from {
+            from + step
+        } else {
+            from - step
+        }
+    }
+
+    /// Converts the clock integer known to fit into a brightness level
+    fn level_from_int<T: TimeInt>(value: T) -> u8 {
+        time::saturating_u32(value).min(u32::from(u8::MAX)) as u8
+    }
+
+    /// Returns the burst length and the whole cycle length of the [`EffectType::Strobe`]
+    fn strobe_lengths<T: TimeInt>(
+        count: u8,
+        flash: Milliseconds<T>,
+        gap: Milliseconds<T>,
+    ) -> (T, T) {
+        let flashes = T::from((u32::from(count) * 2).saturating_sub(1));
+        let burst = flash
+            .integer()
+            .checked_mul(&flashes)
+            .unwrap_or(T::max_value());
+        let cycle_len = burst.checked_add(&gap.integer()).unwrap_or(T::max_value());
+        (burst, cycle_len)
+    }
+
+    /// Creates a [`EffectType::Pulse`] lasting for `duration_ms` milliseconds
+    ///
+    /// Accepts the native integer of the clock, so the pulse can span the whole range of it
+    #[inline]
+    pub fn pulse<C: Clock>(duration_ms: C::T) -> EffectType<C::T> {
+        EffectType::Pulse::<C::T>(Milliseconds::<C::T>::new(duration_ms))
+    }
+
+    /// Creates a [`EffectType::PulseLevel`] at the `level` lasting for `duration_ms`
+    /// milliseconds
+    ///
+    /// Accepts the native integer of the clock
+    #[inline]
+    pub fn pulse_level<C: Clock>(level: u8, duration_ms: C::T) -> EffectType<C::T> {
+        EffectType::PulseLevel {
+            duration: Milliseconds::<C::T>::new(duration_ms),
+            level,
+        }
+    }
+
+    /// Creates a [`EffectType::Fade`] from `from` to `to` level lasting for `duration_ms`
+    /// milliseconds
+    ///
+    /// Accepts the native integer of the clock
+    #[inline]
+    pub fn fade<C: Clock>(from: u8, to: u8, duration_ms: C::T) -> EffectType<C::T> {
+        EffectType::Fade {
+            from,
+            to,
+            duration: Milliseconds::<C::T>::new(duration_ms),
+        }
+    }
+
+    /// Creates a [`EffectType::Strobe`] of `count` flashes lasting for `flash_ms`
+    /// milliseconds separated by `gap_ms` milliseconds long pauses
+    ///
+    /// Accepts the native integer of the clock
+    #[inline]
+    pub fn strobe<C: Clock>(count: u8, flash_ms: C::T, gap_ms: C::T) -> EffectType<C::T> {
+        EffectType::Strobe {
+            count,
+            flash: Milliseconds::<C::T>::new(flash_ms),
+            gap: Milliseconds::<C::T>::new(gap_ms),
+        }
+    }
+
+    /// Creates a [`EffectType::Ramp`] from `min` to `max` level repeating every `period_ms`
+    /// milliseconds
+    ///
+    /// Accepts the native integer of the clock
+    #[inline]
+    pub fn ramp<C: Clock>(min: u8, max: u8, period_ms: C::T, reverse: bool) -> EffectType<C::T> {
+        EffectType::Ramp {
+            min,
+            max,
+            period: Milliseconds::<C::T>::new(period_ms),
+            reverse,
+        }
+    }
+
+    /// Creates a [`EffectType::Solid`] at the `level`
+    ///
+    /// The effect holds the level until cleared, see [`LedEffect::set_duration`]
+    /// for the transient overlays
+    #[inline]
+    pub fn solid<C: Clock>(level: u8) -> EffectType<C::T> {
+        EffectType::Solid::<C::T>(level)
+    }
+
+    /// Creates a [`EffectType::Sos`] with the dots lasting for `unit_ms` milliseconds
+    ///
+    /// Accepts the native integer of the clock
+    #[inline]
+    pub fn sos<C: Clock>(unit_ms: C::T) -> EffectType<C::T> {
+        EffectType::Sos(Milliseconds::<C::T>::new(unit_ms))
+    }
+
+    /// Creates a [`EffectType::BlinkPeriod`] with the full on and off period lasting
+    /// for `period_ms` milliseconds
+    ///
+    /// Accepts the native integer of the clock
+    #[inline]
+    pub fn blink_period<C: Clock>(period_ms: C::T) -> EffectType<C::T> {
+        EffectType::BlinkPeriod(Milliseconds::<C::T>::new(period_ms))
+    }
+
+    /// Creates a [`EffectType::Blink`] at `rate_hz` Hz, e.g. `blink::<C>(1)` is the same
+    /// as `blink_period::<C>(1_000)`
+    ///
+    /// Accepts the native integer of the clock
+    #[inline]
+    pub fn blink<C: Clock>(rate_hz: C::T) -> EffectType<C::T> {
+        EffectType::Blink::<C::T>(Hertz::<C::T>::new(rate_hz))
+    }
+}
+
+/// UI LED
+///
+/// This LED abstraction tracks it's status and provides
+/// an interface for setting visual effects such as blinking
+/// on the LED.
+///
+/// The effects are [`LedEffect`] by default, implementors may accept
+/// custom [`Effect`] implementations instead.
+///
+/// Implementors should own their resources
+/// TODO: implement ability to set the default state for the user
+pub trait Led<C: Clock, E: Effect<C> = LedEffect<C>> {
+    // Indicates whether the current state is on or off
+    fn is_on(&self) -> bool;
+
+    /// Turns on the LED
+    ///
+    /// Has no effect if the LED is already turned on. Implementors should drive
+    /// the hardware right away when possible, the next [poll](#tymethod.poll)
+    /// reconciles the hardware state otherwise.
+    fn turn_on(&mut self);
+
+    /// Turns off the LED
+    ///
+    /// has no effect if the LED is already turned off. The hardware is driven
+    /// the same way as by [turn_on](#tymethod.turn_on).
+    fn turn_off(&mut self);
+
+    /// Toggles the led on/off
+    ///
+    /// The hardware is driven the same way as by [turn_on](#tymethod.turn_on).
+    fn toggle(&mut self);
+
+    /// Sets the effect on this LED instance
+    ///
+    /// By default effect will have infinite duration unless set otherwise by
+    /// [set_effect_duration](#method.set_effect_duration) call
+    ///
+    /// Setting the effect while another one is active will overwrite it on the next
+    /// [poll](#method.poll) call
+    fn set_effect(&mut self, effect: E);
+
+    /// Sets the effect on this LED instance, starting it at `start`
+    ///
+    /// Unlike [set_effect](#tymethod.set_effect), the effect timeline doesn't start
+    /// on the next poll, so the effects set on multiple LEDs with the same `start`
+    /// run in phase regardless of when each LED is polled. `start` should not be
+    /// later than the next poll.
+    fn set_effect_at(&mut self, mut effect: E, start: Instant<C>) {
+        effect.set_started_at(start);
+        self.set_effect(effect);
+    }
+
+    /// Sets the current effect duration on this LED instance
+    ///
+    /// Can be used to prolong current effect duration
+    ///
+    /// Does nothing if no effect is currently in place
+    fn set_effect_duration(&mut self, dur: Milliseconds<C::T>);
+
+    /// Sets the phase offset of the current effect on this LED instance
+    ///
+    /// The offset is applied once the effect starts, see [`LedEffect::set_phase_offset`]
+    ///
+    /// Does nothing if no effect is currently in place
+    fn set_effect_phase_offset(&mut self, offset: Milliseconds<C::T>);
+
+    /// Returns the current LED effect
+    ///
+    /// Returns [`None`] if no effect is in place
+    fn get_effect(&self) -> Option<&E>;
+
+    /// Returns the duration for which the current effect is going to run from `now`
+    ///
+    /// See [`Effect::remaining`]
+    ///
+    /// Returns [`None`] if no effect is in place or if it runs infinitely
+    fn effect_remaining(&self, now: Instant<C>) -> Option<Milliseconds<C::T>> {
+        self.get_effect().and_then(|fx| fx.remaining(now))
+    }
+
+    /// Indicates that the current effect is still running at `now`
+    ///
+    /// Unlike [get_effect](#tymethod.get_effect), which reflects the effect stored
+    /// until the next poll finishes it, this accounts for the effect running out
+    /// by `now` without polling the LED, see [`Effect::remaining`]. Effects running
+    /// infinitely are always active.
+    fn is_effect_active(&self, now: Instant<C>) -> bool {
+        self.get_effect().is_some_and(|fx| {
+            fx.remaining(now)
+                .map_or(true, |remaining| remaining.integer() > C::T::from(0))
+        })
+    }
+
+    /// Returns the kind of the current effect
+    ///
+    /// See [`Effect::kind`]
+    ///
+    /// Returns [`None`] if no effect is in place or if its kind is unknown
+    fn current_effect_kind(&self) -> Option<effects::EffectKind> {
+        self.get_effect().and_then(|fx| fx.kind())
+    }
+
+    /// Clears current the effect
+    ///
+    /// This should also revert the LED to the state it was in
+    /// before the effect took place
+    fn clear_effect(&mut self);
+
+    /// Pauses the current effect at `now`
+    ///
+    /// The LED is left in its current state while the effect is paused.
+    ///
+    /// Does nothing if no effect is currently in place
+    fn pause_effect(&mut self, now: Instant<C>);
+
+    /// Resumes the paused effect at `now`
+    ///
+    /// The effect continues from the same phase it was paused at instead of
+    /// restarting its cycle.
+    ///
+    /// Does nothing if no effect is currently in place
+    fn resume_effect(&mut self, now: Instant<C>);
+
+    /// Polls the LED, updating it's state tracking and hardware state
+    ///
+    /// This must be done in regular intervals in order to make this abstraction
+    /// work properly. There might be limits on what this abstraction can track based
+    /// on how small / large the intervals are.
+    ///
+    /// Returns the [`CycleBoundary`] if the effect entered a new cycle during this
+    /// poll, e.g. on each blink toggle
+    fn poll(&mut self, now: Instant<C>) -> Option<CycleBoundary>;
+}
+
+/// LED with an adjustable brightness
+///
+/// Shared interface of the dimmable and the on/off LEDs, LEDs without dimming
+/// support degrade gracefully to on/off, i.e. they are off at level `0`
+/// and fully on at any other level.
+///
+/// The change is applied to the hardware the same way as by [`Led::turn_on`].
+pub trait Dimmable {
+    /// Sets the brightness level of the LED, level `0` turns the LED off
+    fn set_brightness(&mut self, level: u8);
+
+    /// Returns the brightness level of the LED, `0` if the LED is off
+    fn brightness(&self) -> u8;
+}
+
+/// Snapshot of the [`PinLed`] state
+///
+/// See [`PinLed::snapshot`] and [`PinLed::restore`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LedSnapshot<T: TimeInt = u32> {
+    /// LED is on once no effect is in place
+    pub is_on: bool,
+    /// Current effect
+    pub effect: Option<EffectSnapshot<T>>,
+    /// Output state driven by the current effect
+    pub effect_is_on: bool,
+    /// Priority of the current effect, see [`PinLed::push_effect`]
+    pub effect_priority: u8,
+}
+
+/// Default maximum number of effects suspended by higher priority effects on a single LED
+///
+/// See the `N` parameter of the [`PinLed`]
+pub const EFFECT_STACK_CAPACITY: usize = 4;
+
+/// Pin action of the [`PinLed`] while the LED is off
+///
+/// Applies the same polarity to the LED with and without an effect,
+/// see [`PinLed::with_off_state`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OffState {
+    /// Pin is driven low while off and high while on, e.g. a push-pull pin
+    /// sourcing the LED current
+    #[default]
+    DriveLow,
+    /// Pin is released (set high) while off and driven low while on, e.g. an
+    /// open-drain pin sinking the LED current. The released line is left in the
+    /// high impedance state, so the LED can share it with other open-drain drivers.
+    Release,
+}
+
+/// LED driven by a [`StatefulOutputPin`] of `embedded_hal`
+///
+/// Runs the built-in [`LedEffect`]s by default, custom [`Effect`]
+/// implementations can be used by setting the `E` type parameter.
+///
+/// The effects drive the pin high while lit, whereas the LED without an effect
+/// drives the pin low while on. Setting an [`OffState`] by
+/// [with_off_state](#method.with_off_state) applies a single polarity to both.
+///
+/// The pin is written only when its state actually changes, so the polls are
+/// cheap even for pins behind slow buses such as I2C GPIO expanders.
+///
+/// Up to `N` effects can be suspended on the effect priority stack, see
+/// [push_effect](#method.push_effect). Each slot takes the size of the effect
+/// plus its priority, i.e. 56 bytes for the [`LedEffect`] of a `u32` clock.
+pub struct PinLed<
+    P: StatefulOutputPin,
+    C: Clock,
+    E: Effect<C> = LedEffect<C>,
+    const N: usize = EFFECT_STACK_CAPACITY,
+> {
+    pin: P,
+    pin_state: Option<PinState>,
+    last_transition_at: Option<Instant<C>>,
+    min_hold: Milliseconds<C::T>,
+    synthetic_now: Instant<C>,
+    engine: EffectEngine<C, E>,
+    effect_priority: u8,
+    suspended: Vec<(u8, E), N>,
+    is_on: bool,
+    committed_is_on: bool,
+    poll_gap: PollGapMonitor<C>,
+    retrigger_at: Option<Instant<C>>,
+    off_state: Option<OffState>,
+    did_io: bool,
+}
+
+impl<P: StatefulOutputPin, C: Clock, E: Effect<C>, const N: usize> PinLed<P, C, E, N> {
+    pub fn new(pin: P) -> Self {
+        Self {
+            pin,
+            pin_state: None,
+            last_transition_at: None,
+            min_hold: Milliseconds::<C::T>::new(C::T::from(0)),
+            synthetic_now: Instant::<C>::new(C::T::from(0)),
+            engine: EffectEngine::new(),
+            effect_priority: 0,
+            suspended: Vec::new(),
+            is_on: false,
+            committed_is_on: false,
+            poll_gap: PollGapMonitor::new(),
+            retrigger_at: None,
+            off_state: None,
+            did_io: false,
+        }
+    }
+
+    /// Sets the pin action while the LED is off
+    ///
+    /// See [set_off_state](#method.set_off_state)
+    pub fn with_off_state(mut self, off_state: OffState) -> Self {
+        self.set_off_state(Some(off_state));
+        self
+    }
+
+    /// Sets the pin action while the LED is off
+    ///
+    /// The pin is driven to the opposite level while the LED is on, use
+    /// [`OffState::Release`] for the open-drain pins on a shared line.
+    /// Passing [`None`] (default) keeps the polarities described at [`PinLed`].
+    /// The pin is rewritten on the next poll.
+    pub fn set_off_state(&mut self, off_state: Option<OffState>) {
+        self.off_state = off_state;
+        self.pin_state = None;
+    }
+
+    /// Returns the pin action while the LED is off
+    pub fn off_state(&self) -> Option<OffState> {
+        self.off_state
+    }
+
+    /// Sets the minimum time the LED holds its physical state
+    ///
+    /// See [set_min_hold](#method.set_min_hold)
+    pub fn with_min_hold(mut self, min_hold: Milliseconds<C::T>) -> Self {
+        self.set_min_hold(min_hold);
+        self
+    }
+
+    /// Sets the minimum time the LED holds its physical state
+    ///
+    /// Without an effect, the LED state change requested by [`Led::turn_on`],
+    /// [`Led::turn_off`] or [`Led::toggle`] is delayed until the LED has been
+    /// in its current state for at least `min_hold`. Smooths out the flicker caused
+    /// by bursty toggling, e.g. on the TX/RX activity LEDs.
+    ///
+    /// Zero hold time (default) applies the changes right away.
+    pub fn set_min_hold(&mut self, min_hold: Milliseconds<C::T>) {
+        self.min_hold = min_hold;
+    }
+
+    /// Returns the state the LED has been committed to, i.e. whether it is lit
+    ///
+    /// Unlike [`Led::is_on`], which reports the requested state right away, follows
+    /// the pin as written by the polls. The state change held back by the
+    /// [minimum hold time](#method.set_min_hold) is reported once it's visible,
+    /// as is the output of the effects, so e.g. a display mirroring the LED
+    /// doesn't show the rapid toggles the LED filters out.
+    pub fn committed_is_on(&self) -> bool {
+        self.committed_is_on
+    }
+
+    /// Sets the maximum gap between two successive polls, checked in the debug builds
+    ///
+    /// See [set_max_poll_gap](#method.set_max_poll_gap)
+    pub fn with_max_poll_gap(mut self, max_gap: Milliseconds<C::T>) -> Self {
+        self.set_max_poll_gap(Some(max_gap));
+        self
+    }
+
+    /// Sets the maximum gap between two successive polls, see [`PollGapMonitor::set_max_gap`]
+    pub fn set_max_poll_gap(&mut self, max_gap: Option<Milliseconds<C::T>>) {
+        self.poll_gap.set_max_gap(max_gap);
+    }
+
+    /// Indicates that the LED has been polled less often than the maximum poll gap
+    ///
+    /// Latched until cleared by [take_poll_gap_exceeded](#method.take_poll_gap_exceeded).
+    /// Always `false` in the release builds.
+    pub fn poll_gap_exceeded(&self) -> bool {
+        self.poll_gap.has_exceeded()
+    }
+
+    /// Returns the latched exceeded poll gap and clears it
+    pub fn take_poll_gap_exceeded(&mut self) -> bool {
+        self.poll_gap.take_exceeded()
+    }
+
+    /// Pushes the effect onto the effect priority stack
+    ///
+    /// Effect with priority higher or equal to the current effect overrides it,
+    /// the overridden effect is suspended until the new one is cleared or completes.
+    /// Effect with lower priority is suspended right away and takes place once
+    /// all higher priority effects are gone.
+    ///
+    /// Suspended effects keep running on their own timeline, so an effect with
+    /// a duration may run out while being suspended.
+    ///
+    /// Returns the effect back if there is no space left to suspend an effect,
+    /// i.e. `N` effects are suspended already, see [`PinLed`].
+    pub fn push_effect(&mut self, effect: E, priority: u8) -> Result<(), E> {
+        if self.engine.is_active() && priority < self.effect_priority {
+            return self.suspend(effect, priority);
+        }
+
+        if let Some(current) = self.engine.take_effect() {
+            if let Err(current) = self.suspend(current, self.effect_priority) {
+                self.engine.set_effect(current);
+                return Err(effect);
+            }
+        }
+        self.engine.set_effect(effect);
+        self.effect_priority = priority;
+        Ok(())
+    }
+
+    /// Returns the priority of the current effect
+    ///
+    /// Returns [`None`] if no effect is in place
+    pub fn effect_priority(&self) -> Option<u8> {
+        self.engine.effect().map(|_| self.effect_priority)
+    }
+
+    /// Polls the LED advancing its own synthetic clock by the `elapsed` time
+    ///
+    /// Alternative to [poll](Led::poll) for the platforms without a convenient
+    /// [`Clock`] implementation, e.g. with just a free-running millisecond counter.
+    /// The synthetic clock starts at zero and is independent of the instants passed
+    /// to the other methods, the two ways of polling should not be mixed.
+    pub fn poll_delta(&mut self, elapsed: Milliseconds<C::T>) -> Option<CycleBoundary> {
+        self.synthetic_now = time::advance(self.synthetic_now, elapsed);
+        self.poll(self.synthetic_now)
+    }
+
+    /// Schedules the effect to replace the current one once the clock reaches `at`
+    ///
+    /// The effect is armed by the first poll at or past `at`, e.g. for a deferred
+    /// feedback. Scheduling another effect replaces the pending one.
+    pub fn schedule_effect(&mut self, effect: E, at: Instant<C>) {
+        self.engine.schedule_effect(effect, at);
+    }
+
+    /// Cancels the scheduled effect and returns it
+    ///
+    /// See [schedule_effect](#method.schedule_effect)
+    pub fn cancel_scheduled_effect(&mut self) -> Option<E> {
+        self.engine.cancel_scheduled()
+    }
+
+    /// Sets the one-shot effect unless the previous one is still cooling down
+    ///
+    /// The effect replaces the current one and further triggers are ignored until
+    /// it's over and the `cooldown` has elapsed after it, e.g. so that an activity
+    /// LED keeps blinking visibly instead of staying lit under a burst of events.
+    /// Effects running infinitely block the triggers only for the `cooldown`.
+    ///
+    /// The cooldown is cleared by the first poll or trigger after it's over, so
+    /// a long idle period doesn't block the triggers again once the clock wraps.
+    ///
+    /// Returns `true` if the effect has been set, `false` if it has been ignored
+    pub fn trigger_with_cooldown(
+        &mut self,
+        now: Instant<C>,
+        effect: E,
+        cooldown: Milliseconds<C::T>,
+    ) -> bool {
+        self.expire_cooldown(now);
+        if self.retrigger_at.is_some() {
+            return false;
+        }
+
+        let length = effect
+            .remaining(now)
+            .unwrap_or(Milliseconds::<C::T>::new(C::T::from(0)));
+        let blocked_for = length
+            .integer()
+            .checked_add(&cooldown.integer())
+            .unwrap_or(C::T::max_value());
+        self.retrigger_at = Some(time::advance(now, Milliseconds::<C::T>::new(blocked_for)));
+        self.engine.set_effect(effect);
+        true
+    }
+
+    /// Clears the current effect and all of the suspended effects
+    pub fn clear_all_effects(&mut self) {
+        self.suspended.clear();
+        self.clear_effect();
+    }
+
+    /// Clears the trigger cooldown once it's over
+    ///
+    /// The cooldown is dropped as soon as it's seen over, rather than compared
+    /// on each trigger, as its end would be seen in the future again once it's
+    /// more than half of the clock range behind.
+    fn expire_cooldown(&mut self, now: Instant<C>) {
+        if self
+            .retrigger_at
+            .is_some_and(|at| time::duration_since(now, at).is_some())
+        {
+            self.retrigger_at = None;
+        }
+    }
+
+    /// Indicates that the pin has been written during the last poll
+    ///
+    /// The pin is written only when its state changes, e.g. for verifying that
+    /// the polls don't keep a slow bus of a GPIO expander busy.
+    pub fn did_io(&self) -> bool {
+        self.did_io
+    }
+
+    /// Returns a mutable reference to the LED pin
+    ///
+    /// The pin state cache is dropped, so the next write drives the pin even if
+    /// it has been changed through the reference.
+    pub fn pin_mut(&mut self) -> &mut P {
+        self.pin_state = None;
+        &mut self.pin
+    }
+
+    /// Releases the LED pin
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+
+    /// Writes the pin state of the effect (high while lit), skipping the write
+    /// if the pin is already at that level
+    fn write_pin(&mut self, state: PinState, now: Instant<C>) {
+        let level = self.pin_level(state);
+        self.write_level(level, state == PinState::High, now);
+    }
+
+    /// Writes the pin level of the LED without an effect, see [`write_pin`](Self::write_pin)
+    fn write_idle_level(&mut self, now: Instant<C>) {
+        self.write_level(self.idle_level(), self.is_on, now);
+    }
+
+    /// Writes the pin `level`, tracking the transition instant
+    fn write_level(&mut self, level: PinState, is_lit: bool, now: Instant<C>) {
+        if self.set_level(level, is_lit) {
+            self.last_transition_at = Some(now);
+            self.did_io = true;
+        }
+    }
+
+    /// Writes the pin `level` outside of the polls, skipping the write if the pin
+    /// is already at that level
+    ///
+    /// A failed write leaves the pin level unknown, so the next poll retries it.
+    ///
+    /// Returns `true` if the pin has been written
+    fn set_level(&mut self, level: PinState, is_lit: bool) -> bool {
+        if self.pin_state == Some(level) {
+            self.committed_is_on = is_lit;
+            return false;
+        }
+        if self.pin.set_state(level).is_err() {
+            self.pin_state = None;
+            return false;
+        }
+        self.pin_state = Some(level);
+        self.committed_is_on = is_lit;
+        true
+    }
+
+    /// Returns the pin level of the LED without an effect
+    ///
+    /// Without an [`OffState`] the pin is driven low while on, as in 1.0
+    fn idle_level(&self) -> PinState {
+        match self.off_state {
+            Some(_) => self.pin_level(PinState::from(self.is_on)),
+            None => PinState::from(!self.is_on),
+        }
+    }
+
+    /// Drives the pin to the idle state right away
+    ///
+    /// Skipped while an effect or the minimum hold time is in place, the poll
+    /// reconciles the pin state in such case. The transition instant is tracked
+    /// just for the minimum hold time, so it's left to the polls as well.
+    fn write_idle_pin(&mut self) {
+        if self.engine.is_active() || self.min_hold > Milliseconds::<C::T>::new(C::T::from(0)) {
+            return;
+        }
+        self.set_level(self.idle_level(), self.is_on);
+    }
+
+    /// Maps the pin state of the effect (high while lit) onto the physical pin level
+    fn pin_level(&self, state: PinState) -> PinState {
+        match self.off_state {
+            None | Some(OffState::DriveLow) => state,
+            Some(OffState::Release) => !state,
+        }
+    }
+
+    /// Replaces the current effect with the highest priority suspended effect, if any
+    fn resume_suspended(&mut self) {
+        match self.suspended.pop() {
+            Some((priority, fx)) => {
+                self.engine.set_effect(fx);
+                self.effect_priority = priority;
+            }
+            None => {
+                self.engine.take_effect();
+                self.effect_priority = 0;
+            }
+        }
+    }
+
+    /// Inserts the effect into the suspended effects, keeping them sorted by priority
+    fn suspend(&mut self, effect: E, priority: u8) -> Result<(), E> {
+        let index = self
+            .suspended
+            .iter()
+            .position(|(p, _)| *p > priority)
+            .unwrap_or(self.suspended.len());
+        self.suspended
+            .insert(index, (priority, effect))
+            .map_err(|(_, effect)| effect)
+    }
+}
+
+impl<P: StatefulOutputPin, C: Clock, const N: usize> PinLed<P, C, LedEffect<C>, N> {
+    /// Takes a snapshot of the LED state at `now`
+    ///
+    /// The suspended effects are not captured, see [push_effect](#method.push_effect)
+    pub fn snapshot(&self, now: Instant<C>) -> LedSnapshot<C::T> {
+        LedSnapshot {
+            is_on: self.is_on,
+            effect: self.engine.effect().map(|fx| fx.snapshot(now)),
+            effect_is_on: self.engine.is_on(),
+            effect_priority: self.effect_priority,
+        }
+    }
+
+    /// Restores the LED state from the `snapshot`, rebasing it onto `now`
+    ///
+    /// Replaces the current effect and drops the suspended ones. The restored effect
+    /// continues from the same phase it was captured at.
+    pub fn restore(&mut self, snapshot: LedSnapshot<C::T>, now: Instant<C>) {
+        self.suspended.clear();
+        self.is_on = snapshot.is_on;
+        self.effect_priority = snapshot.effect_priority;
+        match snapshot.effect {
+            Some(fx) => {
+                let fx = LedEffect::from_snapshot(fx, now);
+                self.engine.set_running_effect(fx, snapshot.effect_is_on);
+                // running effect drives the pin only on its transitions
+                self.write_pin(PinState::from(snapshot.effect_is_on), now);
+            }
+            None => {
+                self.engine.take_effect();
+            }
+        }
+    }
+
+    /// Swaps the type of the current effect without restarting it
+    ///
+    /// See [`LedEffect::set_type`](effects::LedEffect::set_type). Does nothing if
+    /// no effect is in place.
+    pub fn update_effect_type(&mut self, fx_type: effects::EffectType<C::T>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.set_type(fx_type);
+        }
+    }
+
+    /// Blips the LED for `duration`, e.g. on every received packet
+    ///
+    /// Replaces the current effect with a single [`EffectType::Pulse`](effects::EffectType::Pulse).
+    /// Calling it again while the blip is still running retriggers it, so the LED
+    /// stays on for `duration` from `now` without flickering in between.
+    pub fn blip(&mut self, now: Instant<C>, duration: Milliseconds<C::T>) {
+        let is_blipping = self.engine.effect().is_some_and(|fx| {
+            fx.has_started() && matches!(fx.get_type(), effects::EffectType::Pulse(_))
+        });
+
+        let mut fx = LedEffect::new(effects::EffectType::Pulse(duration));
+        if is_blipping {
+            // the LED is already on, only restart the pulse timeline
+            fx.set_started_at(now);
+        }
+        self.engine.set_effect(fx);
+    }
+
+    /// Interrupts the current effect with a single pulse lasting `duration`
+    ///
+    /// The current effect is suspended for the pulse and takes over right after it,
+    /// in the phase it would have been in without the interruption, e.g. to
+    /// acknowledge an input within an ongoing status blink. The pulse overrides
+    /// the effects of any priority, see [push_effect](#method.push_effect).
+    ///
+    /// Returns the pulse back if there is no space left to suspend the current effect
+    pub fn overlay_pulse(
+        &mut self,
+        now: Instant<C>,
+        duration: Milliseconds<C::T>,
+    ) -> Result<(), LedEffect<C>> {
+        let mut fx = LedEffect::new(effects::EffectType::Pulse(duration));
+        fx.set_started_at(now);
+        self.push_effect(fx, u8::MAX)
+    }
+}
+
+impl<P: StatefulOutputPin, E: Effect<FrameClock>, const N: usize> PinLed<P, FrameClock, E, N> {
+    /// Polls the LED at the `frame` of a fixed rate render loop
+    ///
+    /// Alternative to [poll](Led::poll) keyed off an incrementing frame counter,
+    /// the effect durations are counted in frames, see [`FrameClock`]. The instants
+    /// passed to the other methods are the frames as well, see [`FrameClock::frame`].
+    /// The counter may wrap around the same way as a clock would.
+    pub fn poll_frame(&mut self, frame: u32) -> Option<CycleBoundary> {
+        self.poll(FrameClock::frame(frame))
+    }
+}
+
+impl<P: StatefulOutputPin, C: Clock, E: Effect<C>, const N: usize> Dimmable for PinLed<P, C, E, N> {
+    fn set_brightness(&mut self, level: u8) {
+        self.is_on = level > 0;
+        self.write_idle_pin();
+    }
+
+    fn brightness(&self) -> u8 {
+        match self.is_on {
+            true => u8::MAX,
+            false => 0,
+        }
+    }
+}
+
+impl<P: StatefulOutputPin, C: Clock, E: Effect<C>, const N: usize> Led<C, E>
+    for PinLed<P, C, E, N>
+{
+    fn is_on(&self) -> bool {
+        self.is_on
+    }
+
+    fn turn_on(&mut self) {
+        self.is_on = true;
+        self.write_idle_pin();
+    }
+
+    fn turn_off(&mut self) {
+        self.is_on = false;
+        self.write_idle_pin();
+    }
+
+    fn toggle(&mut self) {
+        self.is_on = !self.is_on;
+        self.write_idle_pin();
+    }
+
+    fn set_effect(&mut self, effect: E) {
+        self.engine.set_effect(effect);
+    }
+
+    fn set_effect_duration(&mut self, dur: Milliseconds<<C as Clock>::T>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.set_duration(dur)
+        }
+    }
+
+    fn set_effect_phase_offset(&mut self, offset: Milliseconds<C::T>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.set_phase_offset(offset)
+        }
+    }
+
+    /// Clears current the effect
+    ///
+    /// Falls back to the highest priority suspended effect if there is any,
+    /// see [`PinLed::push_effect`].
+    fn clear_effect(&mut self) {
+        self.resume_suspended();
+        self.turn_off();
+    }
+
+    fn pause_effect(&mut self, now: Instant<C>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.pause(now)
+        }
+    }
+
+    fn resume_effect(&mut self, now: Instant<C>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.resume(now)
+        }
+    }
+
+    fn poll(&mut self, now: Instant<C>) -> Option<CycleBoundary> {
+        self.did_io = false;
+        self.poll_gap.record(now);
+        self.expire_cooldown(now);
+        self.engine.arm_scheduled(now);
+        if self.engine.is_active() {
+            // LED has an effect, process effect
+            match self.engine.poll(now) {
+                Some(EffectAction::SetOn) => self.write_pin(PinState::High, now),
+                Some(EffectAction::SetOff) => self.write_pin(PinState::Low, now),
+                Some(EffectAction::SetDuty(level)) => {
+                    // the pin follows the level crossing the half level
+                    self.write_pin(PinState::from(level > u8::MAX / 2), now);
+                }
+                Some(EffectAction::Completed { is_on, .. }) => {
+                    // effect is over, the LED is left in the state requested by the effect
+                    self.resume_suspended();
+                    self.is_on = is_on;
+                    if !self.engine.is_active() {
+                        self.write_idle_level(now);
+                    } else {
+                        // resumed effect takes over within the same poll, starting
+                        // from the output left by the finished one
+                        self.write_pin(PinState::from(is_on), now);
+                        let did_io = self.did_io;
+                        let boundary = self.poll(now);
+                        self.did_io |= did_io;
+                        return boundary;
+                    }
+                }
+                None => {}
+            }
+            self.engine.cycle_boundary()
+        } else {
+            // No effect on led, proceed as normal
+            let is_held = self
+                .last_transition_at
+                .is_some_and(|at| time::elapsed_since(now, at) < self.min_hold);
+            if !is_held {
+                self.write_idle_level(now);
+            }
+            None
+        }
+    }
+
+    fn get_effect(&self) -> Option<&E> {
+        self.engine.effect()
+    }
+}
+
+impl<P: StatefulOutputPin, C: Clock, E: Effect<C>, const N: usize> Poll<C> for PinLed<P, C, E, N> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        Led::poll(self, now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::effects::{self, LedEffect};
+    use super::{Dimmable, Led, OffState, PinLed, EFFECT_STACK_CAPACITY};
+    use crate::mock::{MockClock, MockOutputPin, MockPin};
+    use crate::time::{Instant, Milliseconds};
+
+    type TestLed<'a> = PinLed<MockOutputPin<'a>, MockClock>;
+
+    #[test]
+    fn pulse_lights_on_first_poll_and_ends_after_duration() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output()).with_off_state(OffState::DriveLow);
+
+        led.set_effect(LedEffect::new(effects::pulse::<MockClock>(100)));
+        led.poll(clock.now());
+        assert!(pin.is_high());
+
+        clock.advance(100);
+        led.poll(clock.now());
+        assert!(pin.is_high());
+
+        // the effect is over once the duration is past
+        clock.advance(1);
+        led.poll(clock.now());
+        assert!(!pin.is_high());
+        assert!(!led.is_on());
+    }
+
+    #[test]
+    fn higher_priority_effect_preempts_the_current_one() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+
+        let blink = LedEffect::new(effects::blink_period::<MockClock>(1_000));
+        assert!(led.push_effect(blink, 1).is_ok());
+        led.poll(clock.now());
+        clock.advance(600);
+        led.poll(clock.now());
+        assert!(!pin.is_high());
+
+        let pulse = LedEffect::new(effects::pulse::<MockClock>(100));
+        assert!(led.push_effect(pulse, 5).is_ok());
+        led.poll(clock.now());
+        assert_eq!(led.effect_priority(), Some(5));
+        assert!(pin.is_high());
+
+        // lower priority effect waits for the current one
+        let pulse = LedEffect::new(effects::pulse::<MockClock>(100));
+        assert!(led.push_effect(pulse, 3).is_ok());
+        assert_eq!(led.effect_priority(), Some(5));
+    }
+
+    #[test]
+    fn suspended_effect_resumes_in_its_phase() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+
+        // lit for the first 500 ms of every second
+        let blink = LedEffect::new(effects::blink_period::<MockClock>(1_000));
+        assert!(led.push_effect(blink, 1).is_ok());
+        led.poll(clock.now());
+        clock.advance(600);
+        let pulse = LedEffect::new(effects::pulse::<MockClock>(200));
+        assert!(led.push_effect(pulse, 5).is_ok());
+        led.poll(clock.now());
+
+        // the pulse ends at 801 ms, the blink takes over within the same poll
+        for (at, is_lit) in [(650, true), (850, false), (1_100, true), (1_650, false)] {
+            clock.set(at);
+            led.poll(clock.now());
+            assert_eq!(pin.is_high(), is_lit, "at {} ms", at);
+        }
+        assert_eq!(led.effect_priority(), Some(1));
+    }
+
+    #[test]
+    fn full_effect_stack_returns_the_effect_back() {
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+        let pulse = || LedEffect::new(effects::pulse::<MockClock>(100));
+
+        for priority in 1..=EFFECT_STACK_CAPACITY as u8 + 1 {
+            assert!(led.push_effect(pulse(), priority).is_ok());
+        }
+        assert!(led.push_effect(pulse(), 10).is_err());
+        assert!(led.push_effect(pulse(), 0).is_err());
+        assert_eq!(led.effect_priority(), Some(EFFECT_STACK_CAPACITY as u8 + 1));
+    }
+
+    #[test]
+    fn blink_runs_across_the_clock_wrap() {
+        let clock = MockClock::new();
+        clock.set(u32::MAX - 1_124);
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+
+        let mut fx = LedEffect::new(effects::blink_period::<MockClock>(2_000));
+        fx.set_duration(Milliseconds(3_000));
+        led.set_effect(fx);
+        led.poll(clock.now());
+
+        // sampled every 250 ms, the clock wraps in the fifth step
+        let mut toggles = 0;
+        for step in 1..=12 {
+            clock.advance(250);
+            let was_high = pin.is_high();
+            led.poll(clock.now());
+            toggles += u32::from(pin.is_high() != was_high);
+            let elapsed = led.get_effect().unwrap().time_elapsed(clock.now());
+            assert_eq!(elapsed, Some(Milliseconds(step * 250)));
+        }
+        // toggled once the second has passed at 1250 ms and 2500 ms
+        assert_eq!(toggles, 2);
+
+        // 3250 ms in, the duration is over past the wrap as well
+        clock.advance(250);
+        led.poll(clock.now());
+        assert!(led.get_effect().is_none());
+    }
+
+    #[test]
+    fn idle_polls_do_not_write_the_pin() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+
+        led.poll(clock.now());
+        led.turn_on();
+        led.poll(clock.now());
+        assert_eq!(pin.write_count(), 2);
+        // the pin is driven low while on without an effect
+        assert!(!pin.is_high());
+
+        pin.reset_counters();
+        for _ in 0..10 {
+            clock.advance(1);
+            led.poll(clock.now());
+        }
+        assert_eq!(pin.write_count(), 0);
+    }
+
+    #[test]
+    fn inverted_blink_is_low_exactly_when_the_plain_one_is_high() {
+        let clock = MockClock::new();
+        let (plain_pin, inverted_pin) = (MockPin::new(), MockPin::new());
+        let mut plain = TestLed::new(plain_pin.output());
+        let mut inverted = TestLed::new(inverted_pin.output());
+
+        plain.set_effect(LedEffect::new(effects::blink::<MockClock>(1)));
+        inverted.set_effect(LedEffect::new(effects::blink::<MockClock>(1)).with_inverted(true));
+        for _ in 0..40 {
+            plain.poll(clock.now());
+            inverted.poll(clock.now());
+            assert_ne!(plain_pin.is_high(), inverted_pin.is_high());
+            clock.advance(100);
+        }
+    }
+
+    #[test]
+    fn set_brightness_drives_the_pin_right_away() {
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+
+        // the pin is driven low while on without an effect
+        Dimmable::set_brightness(&mut led, 10);
+        assert!(led.is_on());
+        assert!(!pin.is_high());
+
+        Dimmable::set_brightness(&mut led, 0);
+        assert!(!led.is_on());
+        assert!(pin.is_high());
+    }
+
+    #[test]
+    fn failed_write_is_retried_by_the_poll() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+        led.poll(clock.now());
+
+        pin.set_failing(true);
+        led.turn_on();
+        led.poll(clock.now());
+        assert!(pin.is_high());
+
+        pin.set_failing(false);
+        led.poll(clock.now());
+        assert!(!pin.is_high());
+        assert!(led.committed_is_on());
+    }
+
+    #[test]
+    fn blink_starts_lit_regardless_of_the_pin_level() {
+        for was_on in [false, true] {
+            let clock = MockClock::new();
+            let pin = MockPin::new();
+            let mut led = TestLed::new(pin.output());
+            if was_on {
+                led.turn_on();
+            }
+            led.poll(clock.now());
+            assert_eq!(pin.is_high(), !was_on);
+
+            led.set_effect(LedEffect::new(effects::blink::<MockClock>(1)));
+            led.poll(clock.now());
+            assert!(pin.is_high());
+            clock.advance(501);
+            led.poll(clock.now());
+            assert!(!pin.is_high());
+        }
+    }
+
+    #[test]
+    fn blink_rate_and_period_toggle_every_half_period() {
+        let clock = MockClock::new();
+        let (rate_pin, period_pin) = (MockPin::new(), MockPin::new());
+        let mut rate_led = TestLed::new(rate_pin.output());
+        let mut period_led = TestLed::new(period_pin.output());
+
+        rate_led.set_effect(LedEffect::new(effects::blink::<MockClock>(1)));
+        period_led.set_effect(LedEffect::new(effects::blink_period::<MockClock>(1_000)));
+        // polled every 5 ms, sampled in the middle of each 250 ms step
+        for at in (0..2_000).step_by(5) {
+            clock.set(at);
+            rate_led.poll(clock.now());
+            period_led.poll(clock.now());
+            if at % 250 == 125 {
+                // on for 500 ms, off for 500 ms
+                assert_eq!(rate_pin.is_high(), at % 1_000 < 500, "at {} ms", at);
+                assert_eq!(period_pin.is_high(), rate_pin.is_high());
+            }
+        }
+    }
+
+    /// Runs the effect until it's over, checking that `is_on` follows the pin afterwards
+    ///
+    /// The LED without an effect drives the pin low while on
+    fn assert_consistent_after(effect: LedEffect<MockClock>, was_on: bool) {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+        if was_on {
+            led.turn_on();
+        }
+
+        led.set_effect(effect);
+        while led.get_effect().is_some() {
+            led.poll(clock.now());
+            clock.advance(10);
+        }
+        assert_eq!(led.is_on(), !pin.is_high());
+        assert_eq!(led.committed_is_on(), led.is_on());
+
+        // the following idle polls don't fight the state left by the effect
+        pin.reset_counters();
+        led.poll(clock.now());
+        assert_eq!(pin.write_count(), 0);
+        assert_eq!(led.is_on(), !pin.is_high());
+    }
+
+    #[test]
+    fn is_on_follows_the_pin_after_the_effects() {
+        for was_on in [false, true] {
+            assert_consistent_after(LedEffect::new(effects::pulse::<MockClock>(100)), was_on);
+
+            let mut blink = LedEffect::new(effects::blink::<MockClock>(2));
+            blink.set_duration(Milliseconds(1_100));
+            assert_consistent_after(blink, was_on);
+
+            let fade = LedEffect::new(effects::fade::<MockClock>(0, u8::MAX, 300));
+            assert_consistent_after(fade, was_on);
+
+            let mut ramp = LedEffect::new(effects::ramp::<MockClock>(0, u8::MAX, 200, false));
+            ramp.set_duration(Milliseconds(300));
+            assert_consistent_after(ramp, was_on);
+        }
+    }
+
+    #[test]
+    fn scheduled_effect_arms_after_a_long_poll_gap() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+
+        led.schedule_effect(
+            LedEffect::new(effects::blink::<MockClock>(100)),
+            Instant::new(1_000),
+        );
+        led.poll(clock.now());
+        assert!(led.get_effect().is_none());
+
+        // the due instant is over half of the clock range behind the next poll
+        clock.set(1_000 + (1 << 31) + 1);
+        led.poll(clock.now());
+        assert!(led.get_effect().is_some());
+    }
+
+    #[test]
+    fn cooldown_expires_across_half_of_the_clock_range() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+        let pulse = || LedEffect::new(effects::pulse::<MockClock>(100));
+        let cooldown = Milliseconds(50);
+
+        assert!(led.trigger_with_cooldown(clock.now(), pulse(), cooldown));
+        clock.advance(100);
+        assert!(!led.trigger_with_cooldown(clock.now(), pulse(), cooldown));
+
+        clock.advance(100);
+        led.poll(clock.now());
+        // idle for longer than half of the clock range, the cooldown end looks ahead again
+        clock.advance(u32::MAX / 2 + 1_000);
+        assert!(led.trigger_with_cooldown(clock.now(), pulse(), cooldown));
+    }
+
+    #[test]
+    fn off_state_applies_one_polarity_with_and_without_an_effect() {
+        for (off_state, lit_is_high) in [(OffState::DriveLow, true), (OffState::Release, false)] {
+            let clock = MockClock::new();
+            let pin = MockPin::new();
+            let mut led = TestLed::new(pin.output()).with_off_state(off_state);
+
+            led.turn_on();
+            assert_eq!(pin.is_high(), lit_is_high);
+            led.turn_off();
+            assert_eq!(pin.is_high(), !lit_is_high);
+
+            led.set_effect(LedEffect::new(effects::pulse::<MockClock>(100)));
+            led.poll(clock.now());
+            assert_eq!(pin.is_high(), lit_is_high);
+            clock.advance(101);
+            led.poll(clock.now());
+            assert_eq!(pin.is_high(), !lit_is_high);
+        }
+    }
+
+    #[test]
+    fn coarse_polls_keep_the_fast_blink_phase() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+
+        // toggles every 50 ms, polled every 130 ms
+        led.set_effect(LedEffect::new(effects::blink_period::<MockClock>(100)));
+        led.poll(clock.now());
+        for poll in 1..=50u32 {
+            clock.advance(130);
+            led.poll(clock.now());
+            // each half period ends once it's run past
+            let half_periods = (poll * 130 - 1) / 50;
+            assert_eq!(pin.is_high(), half_periods % 2 == 0, "at {} ms", poll * 130);
+        }
+    }
+
+    #[test]
+    fn u64_microsecond_clock_runs_an_effect_for_a_day() {
+        type MicrosClock = MockClock<u64, 1_000_000>;
+        const DAY_MS: u64 = 24 * 60 * 60 * 1_000;
+
+        let clock = MicrosClock::new();
+        let pin = MockPin::new();
+        let mut led = PinLed::<MockOutputPin, MicrosClock>::new(pin.output());
+
+        let mut fx = LedEffect::new(effects::blink_period::<MicrosClock>(2_000));
+        fx.set_duration(Milliseconds(DAY_MS));
+        led.set_effect(fx);
+        led.poll(clock.now());
+
+        // sampled a quarter into the lit half of every minute
+        clock.advance(250_000);
+        for minute in 0..24 * 60 {
+            led.poll(clock.now());
+            assert!(pin.is_high(), "at minute {}", minute);
+            let elapsed = led.get_effect().unwrap().time_elapsed(clock.now());
+            assert_eq!(elapsed, Some(Milliseconds(minute * 60_000 + 250)));
+            clock.advance(60_000_000);
+        }
+
+        clock.set(DAY_MS * 1_000);
+        led.poll(clock.now());
+        assert!(led.get_effect().is_some());
+        clock.advance(1_000);
+        led.poll(clock.now());
+        assert!(led.get_effect().is_none());
+        assert_eq!(led.is_on(), !pin.is_high());
+    }
+
+    #[test]
+    fn overlay_pulse_resumes_the_interrupted_blink_in_phase() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.output());
+
+        led.set_effect(LedEffect::new(effects::blink_period::<MockClock>(1_000)));
+        led.poll(clock.now());
+        clock.set(300);
+        assert!(led.overlay_pulse(clock.now(), Milliseconds(400)).is_ok());
+
+        for (at, is_lit) in [(350, true), (650, true), (750, false), (1_100, true)] {
+            clock.set(at);
+            led.poll(clock.now());
+            assert_eq!(pin.is_high(), is_lit, "at {} ms", at);
+        }
     }
 }
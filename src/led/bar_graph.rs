@@ -0,0 +1,155 @@
+use num_traits::CheckedMul;
+
+use super::{Dimmable, Led};
+use crate::poll::{Poll, PollError};
+use crate::time::{self, Clock, FixedPoint, Instant, Milliseconds};
+
+/// Brightness units of a single fully lit LED
+const LED_UNITS: u32 = u8::MAX as u32;
+
+/// Bar graph showing a level on a row of LEDs
+///
+/// Lights up a number of LEDs proportional to the level, starting from the first
+/// one. The topmost lit LED is partially dimmed to show the remainder, LEDs without
+/// dimming support light it up fully, see [`Dimmable`].
+///
+/// The level changes can be optionally animated by sweeping, see
+/// [with_sweep](#method.with_sweep).
+pub struct BarGraph<L: Led<C> + Dimmable, C: Clock, const N: usize> {
+    leds: [L; N],
+    level: u8,
+    shown: u32,
+    sweep: Option<Milliseconds<C::T>>,
+    last_poll_at: Option<Instant<C>>,
+}
+
+impl<L: Led<C> + Dimmable, C: Clock, const N: usize> BarGraph<L, C, N> {
+    /// Create new [`BarGraph`] from the passed in LEDs, showing zero level
+    ///
+    /// The first LED is the bottom of the bar
+    pub fn new(leds: [L; N]) -> Self {
+        let mut graph = Self {
+            leds,
+            level: 0,
+            shown: 0,
+            sweep: None,
+            last_poll_at: None,
+        };
+        graph.apply();
+        graph
+    }
+
+    /// Enables sweeping between the levels
+    ///
+    /// See [set_sweep](#method.set_sweep)
+    pub fn with_sweep(mut self, per_led: Milliseconds<C::T>) -> Self {
+        self.set_sweep(Some(per_led));
+        self
+    }
+
+    /// Sets the time the sweep takes to cross a single LED
+    ///
+    /// The shown level moves towards the level set by [set_level](#method.set_level)
+    /// gradually on each poll instead of jumping to it right away.
+    ///
+    /// Passing [`None`] (default) disables the sweeping.
+    pub fn set_sweep(&mut self, per_led: Option<Milliseconds<C::T>>) {
+        self.sweep = per_led;
+    }
+
+    /// Sets the level in percent, clamped to `100`
+    pub fn set_level(&mut self, percent: u8) {
+        self.level = percent.min(100);
+        if self.sweep.is_none() {
+            self.shown = self.target();
+            self.apply();
+        }
+    }
+
+    /// Returns the level in percent
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Indicates that the sweep towards the level is in progress
+    pub fn is_sweeping(&self) -> bool {
+        self.shown != self.target()
+    }
+
+    /// Returns the LED at `index`
+    ///
+    /// Returns [`None`] if the index is out of bounds
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut L> {
+        self.leds.get_mut(index)
+    }
+
+    /// Releases the LEDs
+    pub fn into_inner(self) -> [L; N] {
+        self.leds
+    }
+
+    /// Polls the bar graph, advancing the sweep and polling all LEDs
+    pub fn poll(&mut self, now: Instant<C>) {
+        let target = self.target();
+        if self.shown != target {
+            let elapsed = self
+                .last_poll_at
+                .map_or(Milliseconds::<C::T>::new(C::T::from(0)), |at| {
+                    time::elapsed_since(now, at)
+                });
+            let step = self.sweep_step(elapsed);
+            self.shown = if self.shown < target {
+                self.shown.saturating_add(step).min(target)
+            } else {
+                self.shown.saturating_sub(step).max(target)
+            };
+            self.apply();
+        }
+        self.last_poll_at = Some(now);
+
+        for led in self.leds.iter_mut() {
+            led.poll(now);
+        }
+    }
+
+    /// Returns the brightness units the sweep moves by over the `elapsed` time
+    fn sweep_step(&self, elapsed: Milliseconds<C::T>) -> u32 {
+        let Some(per_led) = self.sweep else {
+            return u32::MAX;
+        };
+        let (elapsed, per_led) = (elapsed.integer(), per_led.integer());
+        if per_led == C::T::from(0) {
+            return u32::MAX;
+        }
+
+        let units = C::T::from(LED_UNITS);
+        let step = match elapsed.checked_mul(&units) {
+            Some(scaled) => scaled / per_led,
+            None => elapsed / (per_led / units).max(C::T::from(1)),
+        };
+        time::saturating_u32(step)
+    }
+
+    /// Returns the brightness units of the level
+    fn target(&self) -> u32 {
+        // saturates only for the bars of hundreds of thousands of LEDs
+        (u32::from(self.level) * LED_UNITS).saturating_mul(N as u32) / 100
+    }
+
+    /// Sets the brightness of the LEDs according to the shown level
+    fn apply(&mut self) {
+        let mut remaining = self.shown;
+        for led in self.leds.iter_mut() {
+            let units = remaining.min(LED_UNITS);
+            remaining -= units;
+            led.set_brightness(units as u8);
+        }
+    }
+}
+
+impl<L: Led<C> + Dimmable, C: Clock, const N: usize> Poll<C> for BarGraph<L, C, N> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now);
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use num_traits::CheckedMul;
+
+use super::LedGroup;
+use crate::poll::{Poll, PollError};
+use crate::time::{self, Clock, FixedPoint, Instant, Milliseconds};
+
+/// Direction in which the [`ChaseEffect`] sweeps across the LEDs
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChaseDirection {
+    /// Sweep from the first LED to the last one, then wrap around to the first
+    Forward,
+    /// Sweep from the last LED to the first one, then wrap around to the last
+    Reverse,
+    /// Sweep back and forth between the first and the last LED (Knight-Rider)
+    Bounce,
+}
+
+/// Chase sequencer lighting up LEDs of a [`LedGroup`] one at a time
+///
+/// Only a single LED of the group is lit at any time. The lit LED advances
+/// every `dwell` period in the configured [`ChaseDirection`].
+pub struct ChaseEffect<'a, C: Clock, const N: usize> {
+    group: LedGroup<'a, C, N>,
+    dwell: Milliseconds<C::T>,
+    direction: ChaseDirection,
+    is_running: bool,
+    started_at: Option<Instant<C>>,
+    lit: Option<usize>,
+}
+
+impl<'a, C: Clock, const N: usize> ChaseEffect<'a, C, N> {
+    /// Create new [`ChaseEffect`] sweeping across the `group`
+    ///
+    /// The sweep starts on the next [poll](#method.poll) call
+    pub fn new(
+        group: LedGroup<'a, C, N>,
+        dwell: Milliseconds<C::T>,
+        direction: ChaseDirection,
+    ) -> Self {
+        let mut chase = Self {
+            group,
+            dwell,
+            direction,
+            is_running: true,
+            started_at: None,
+            lit: None,
+        };
+        chase.set_dwell(dwell);
+        chase
+    }
+
+    /// Sets the duration for which each LED stays lit
+    ///
+    /// Zero duration is treated as one millisecond
+    pub fn set_dwell(&mut self, dwell: Milliseconds<C::T>) {
+        self.dwell = if dwell.integer() == C::T::from(0) {
+            Milliseconds::<C::T>::new(C::T::from(1))
+        } else {
+            dwell
+        };
+    }
+
+    /// Sets the sweep direction
+    ///
+    /// The sweep is restarted from the beginning on the next poll
+    pub fn set_direction(&mut self, direction: ChaseDirection) {
+        self.direction = direction;
+        self.started_at = None;
+    }
+
+    /// Returns the sweep direction
+    pub fn direction(&self) -> ChaseDirection {
+        self.direction
+    }
+
+    /// Returns the index of the currently lit LED
+    pub fn position(&self) -> Option<usize> {
+        self.lit
+    }
+
+    /// Indicates whether the sweep is running
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    /// Starts the sweep from the beginning on the next poll
+    pub fn start(&mut self) {
+        self.is_running = true;
+        self.started_at = None;
+    }
+
+    /// Stops the sweep and turns off all LEDs of the group
+    ///
+    /// Can be called mid-sweep, the LEDs are turned off right away
+    pub fn clear(&mut self) {
+        self.is_running = false;
+        self.started_at = None;
+        self.lit = None;
+        self.group.turn_off_all();
+    }
+
+    /// Releases the underlying [`LedGroup`]
+    pub fn into_inner(self) -> LedGroup<'a, C, N> {
+        self.group
+    }
+
+    /// Polls the sequencer, advancing the lit LED and polling the whole group
+    pub fn poll(&mut self, now: Instant<C>) {
+        if self.is_running && N > 0 {
+            // the sweep is re-anchored every cycle, so the elapsed time never saturates
+            let cycle = self
+                .dwell
+                .integer()
+                .checked_mul(&C::T::from(self.cycle_len() as u32))
+                .unwrap_or(C::T::from(0));
+            let started_at = self.started_at.get_or_insert(now);
+            let elapsed = time::realign(started_at, now, Milliseconds::<C::T>::new(cycle));
+            let step = elapsed.integer() / self.dwell.integer();
+
+            let position = self.position_at(step);
+            if self.lit != Some(position) {
+                if let Some(led) = self.lit.and_then(|i| self.group.get_mut(i)) {
+                    led.turn_off();
+                }
+                if let Some(led) = self.group.get_mut(position) {
+                    led.turn_on();
+                }
+                self.lit = Some(position);
+            }
+        }
+
+        self.group.poll(now);
+    }
+
+    /// Returns the number of dwell periods of a whole sweep cycle
+    fn cycle_len(&self) -> usize {
+        match self.direction {
+            ChaseDirection::Bounce if N > 1 => 2 * N - 2,
+            _ => N,
+        }
+    }
+
+    /// Maps the number of elapsed dwell periods onto the LED index
+    fn position_at(&self, step: C::T) -> usize {
+        let cycle_len = self.cycle_len();
+        let phase = time::saturating_u32(step % C::T::from(cycle_len as u32)) as usize;
+
+        match self.direction {
+            ChaseDirection::Forward => phase,
+            ChaseDirection::Reverse => N - 1 - phase,
+            ChaseDirection::Bounce if phase < N => phase,
+            ChaseDirection::Bounce => cycle_len - phase,
+        }
+    }
+}
+
+impl<'a, C: Clock, const N: usize> Poll<C> for ChaseEffect<'a, C, N> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChaseDirection, ChaseEffect};
+    use crate::led::{LedGroup, PinLed};
+    use crate::mock::{MockClock, MockOutputPin, MockPin};
+    use crate::time::Milliseconds;
+
+    type MicrosClock = MockClock<u32, 1_000_000>;
+
+    #[test]
+    fn keeps_sweeping_past_half_of_the_clock_range() {
+        let clock = MicrosClock::new();
+        let pins = [MockPin::new(), MockPin::new(), MockPin::new()];
+        let mut leds: [PinLed<MockOutputPin, MicrosClock>; 3] =
+            core::array::from_fn(|i| PinLed::new(pins[i].output()));
+        let [a, b, c] = &mut leds;
+        let group = LedGroup::new([a, b, c]);
+        let mut chase = ChaseEffect::new(group, Milliseconds(100), ChaseDirection::Forward);
+
+        // ~42 minutes of a microsecond clock, past half of its range
+        for _ in 0..250 {
+            chase.poll(clock.now());
+            clock.advance(10_000_000);
+        }
+
+        chase.poll(clock.now());
+        let position = chase.position().unwrap();
+        for step in 1..=3 {
+            clock.advance(100_000);
+            chase.poll(clock.now());
+            assert_eq!(chase.position(), Some((position + step) % 3));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use core::marker::PhantomData;
+
+use super::effects::{DesiredPinState, Effect, LedEffect};
+use crate::time::{self, Clock, Instant};
+
+/// Output action requested by the [`EffectEngine`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EffectAction {
+    /// Turn the output on
+    SetOn,
+    /// Turn the output off
+    SetOff,
+    /// Set the output to the brightness level
+    SetDuty(u8),
+    /// Effect is over and has been removed from the engine, the output
+    /// should be left in the passed state and optionally at the passed level
+    Completed { is_on: bool, level: Option<u8> },
+}
+
+/// Effect cycle boundary reported by the polls
+///
+/// Marks the start of a new effect cycle, e.g. each blink toggle, so that other
+/// hardware can be kept in sync with the effect timebase.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CycleBoundary {
+    /// Index of the started cycle, counted from zero at the effect start
+    pub cycle: u32,
+    /// Output state at the start of the cycle
+    pub is_on: bool,
+}
+
+/// Effect timing state machine independent of the output hardware
+///
+/// Drives the effect timeline and translates it into [`EffectAction`]s, so
+/// that the outputs (LEDs, buzzers, etc.) only have to apply them to the hardware.
+pub struct EffectEngine<C: Clock, E: Effect<C> = LedEffect<C>> {
+    effect: Option<E>,
+    scheduled: Option<(E, Instant<C>)>,
+    last_armed_check: Option<Instant<C>>,
+    is_on: bool,
+    cycle_boundary: Option<CycleBoundary>,
+    clock: PhantomData<C>,
+}
+
+impl<C: Clock, E: Effect<C>> EffectEngine<C, E> {
+    /// Create new idle [`EffectEngine`]
+    pub fn new() -> Self {
+        Self {
+            effect: None,
+            scheduled: None,
+            last_armed_check: None,
+            is_on: false,
+            cycle_boundary: None,
+            clock: PhantomData,
+        }
+    }
+
+    /// Sets the effect, replacing the current one
+    pub fn set_effect(&mut self, effect: E) {
+        self.effect = Some(effect);
+    }
+
+    /// Sets an already running effect along with the output state it drives,
+    /// e.g. when restoring the effect from a snapshot
+    pub fn set_running_effect(&mut self, effect: E, is_on: bool) {
+        self.effect = Some(effect);
+        self.is_on = is_on;
+    }
+
+    /// Schedules the effect to replace the current one once the clock reaches `at`
+    ///
+    /// Replaces the effect scheduled before, if any. The effect is armed by
+    /// [arm_scheduled](#method.arm_scheduled) and starts on the following poll.
+    pub fn schedule_effect(&mut self, effect: E, at: Instant<C>) {
+        self.scheduled = Some((effect, at));
+    }
+
+    /// Cancels the scheduled effect and returns it
+    pub fn cancel_scheduled(&mut self) -> Option<E> {
+        self.scheduled.take().map(|(effect, _)| effect)
+    }
+
+    /// Returns the instant the scheduled effect is due at
+    ///
+    /// Returns [`None`] if no effect is scheduled
+    pub fn scheduled_at(&self) -> Option<Instant<C>> {
+        self.scheduled.as_ref().map(|(_, at)| *at)
+    }
+
+    /// Replaces the current effect with the scheduled one if it's due at `now`
+    ///
+    /// The due instant passed since the previous call counts as due as well, so
+    /// the effect is armed even after a poll gap of over half of the clock range.
+    ///
+    /// Returns `true` if the scheduled effect has been armed
+    pub fn arm_scheduled(&mut self, now: Instant<C>) -> bool {
+        let since = self.last_armed_check.replace(now);
+        let is_due = self.scheduled.as_ref().is_some_and(|(_, at)| {
+            time::duration_since(now, *at).is_some()
+                || since.is_some_and(|since| time::is_within(*at, since, now))
+        });
+        if !is_due {
+            return false;
+        }
+        self.effect = self.scheduled.take().map(|(effect, _)| effect);
+        true
+    }
+
+    /// Returns the current effect
+    ///
+    /// Returns [`None`] if no effect is in place
+    pub fn effect(&self) -> Option<&E> {
+        self.effect.as_ref()
+    }
+
+    /// Returns the current effect for modification
+    ///
+    /// Returns [`None`] if no effect is in place
+    pub fn effect_mut(&mut self) -> Option<&mut E> {
+        self.effect.as_mut()
+    }
+
+    /// Removes the current effect and returns it
+    pub fn take_effect(&mut self) -> Option<E> {
+        self.effect.take()
+    }
+
+    /// Indicates that an effect is in place
+    pub fn is_active(&self) -> bool {
+        self.effect.is_some()
+    }
+
+    /// Indicates that the effect drives the output on
+    pub fn is_on(&self) -> bool {
+        self.is_on
+    }
+
+    /// Returns the cycle boundary crossed during the last poll
+    ///
+    /// The effect start counts as the boundary of the first cycle
+    pub fn cycle_boundary(&self) -> Option<CycleBoundary> {
+        self.cycle_boundary
+    }
+
+    /// Advances the current effect to `now`
+    ///
+    /// Returns the action to be applied to the output, or [`None`] if the output
+    /// should be left as is or if no effect is in place
+    pub fn poll(&mut self, now: Instant<C>) -> Option<EffectAction> {
+        self.cycle_boundary = None;
+        let fx = self.effect.as_mut()?;
+        let prev_cycle = fx.cycle();
+        let state = fx.update(now, self.is_on);
+        let level = fx.level(now);
+        let cycle = fx.cycle().filter(|cycle| prev_cycle != Some(*cycle));
+
+        match state {
+            DesiredPinState::On => self.is_on = true,
+            DesiredPinState::Off => self.is_on = false,
+            DesiredPinState::Keep => {}
+            DesiredPinState::Finished { is_on } => {
+                self.effect = None;
+                self.is_on = is_on;
+                return Some(EffectAction::Completed {
+                    is_on,
+                    level: level.filter(|_| is_on),
+                });
+            }
+        }
+
+        let action = match (state, level) {
+            (_, Some(level)) => Some(EffectAction::SetDuty(level)),
+            (DesiredPinState::On, None) => Some(EffectAction::SetOn),
+            (DesiredPinState::Off, None) => Some(EffectAction::SetOff),
+            _ => None,
+        };
+        self.cycle_boundary = cycle.map(|cycle| CycleBoundary {
+            cycle,
+            is_on: self.is_on,
+        });
+        action
+    }
+}
+
+impl<C: Clock, E: Effect<C>> Default for EffectEngine<C, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,136 @@
+use num_traits::{Bounded, CheckedAdd};
+
+use super::effects::LedEffect;
+use super::Led;
+use crate::poll::{Poll, PollError};
+use crate::time::{Clock, FixedPoint, Instant, Milliseconds};
+
+/// Group of LEDs driven in unison
+///
+/// Convenience layer on top of the [`Led`] trait forwarding effects
+/// and polls to every member of the group, so that multiple LEDs can be
+/// managed with a single call.
+pub struct LedGroup<'a, C: Clock, const N: usize> {
+    leds: [&'a mut dyn Led<C>; N],
+}
+
+impl<'a, C: Clock, const N: usize> LedGroup<'a, C, N> {
+    /// Create new [`LedGroup`] from the passed in LEDs
+    pub fn new(leds: [&'a mut dyn Led<C>; N]) -> Self {
+        Self { leds }
+    }
+
+    /// Returns the number of LEDs in the group
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Indicates whether the group has no members
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Returns the group member at `index`
+    ///
+    /// Returns [`None`] if the index is out of bounds
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut dyn Led<C>> {
+        match self.leds.get_mut(index) {
+            Some(led) => Some(&mut **led),
+            None => None,
+        }
+    }
+
+    /// Turns on all LEDs in the group
+    pub fn turn_on_all(&mut self) {
+        for led in self.leds.iter_mut() {
+            led.turn_on();
+        }
+    }
+
+    /// Turns off all LEDs in the group
+    pub fn turn_off_all(&mut self) {
+        for led in self.leds.iter_mut() {
+            led.turn_off();
+        }
+    }
+
+    /// Sets the same effect on all LEDs in the group
+    ///
+    /// See [`Led::set_effect`]
+    pub fn set_effect_all(&mut self, effect: LedEffect<C>)
+    where
+        LedEffect<C>: Clone,
+    {
+        for led in self.leds.iter_mut() {
+            led.set_effect(effect.clone());
+        }
+    }
+
+    /// Sets the same effect on all LEDs in the group, starting it at `start`
+    ///
+    /// See [`Led::set_effect_at`]
+    pub fn set_effect_all_at(&mut self, effect: LedEffect<C>, start: Instant<C>)
+    where
+        LedEffect<C>: Clone,
+    {
+        for led in self.leds.iter_mut() {
+            led.set_effect_at(effect.clone(), start);
+        }
+    }
+
+    /// Sets the same effect on all LEDs in the group, each one shifted by `step`
+    /// further into the effect than the previous one
+    ///
+    /// The first LED runs without an offset, e.g. identical blinks turn into
+    /// a marquee running from the last LED to the first. See
+    /// [`LedEffect::set_phase_offset`](super::effects::LedEffect::set_phase_offset)
+    pub fn set_effect_staggered(&mut self, effect: LedEffect<C>, step: Milliseconds<C::T>)
+    where
+        LedEffect<C>: Clone,
+    {
+        let mut offset = C::T::from(0);
+        for led in self.leds.iter_mut() {
+            let fx = effect
+                .clone()
+                .with_phase_offset(Milliseconds::<C::T>::new(offset));
+            led.set_effect(fx);
+            offset = offset
+                .checked_add(&step.integer())
+                .unwrap_or(C::T::max_value());
+        }
+    }
+
+    /// Sets the current effect duration on all LEDs in the group
+    ///
+    /// See [`Led::set_effect_duration`]
+    pub fn set_effect_duration_all(&mut self, dur: Milliseconds<C::T>) {
+        for led in self.leds.iter_mut() {
+            led.set_effect_duration(dur);
+        }
+    }
+
+    /// Clears the effects of all LEDs in the group
+    ///
+    /// See [`Led::clear_effect`]
+    pub fn clear_all(&mut self) {
+        for led in self.leds.iter_mut() {
+            led.clear_effect();
+        }
+    }
+
+    /// Polls all LEDs in the group with the same timestamp
+    ///
+    /// See [`Led::poll`]
+    pub fn poll(&mut self, now: Instant<C>) {
+        for led in self.leds.iter_mut() {
+            led.poll(now);
+        }
+    }
+}
+
+impl<'a, C: Clock, const N: usize> Poll<C> for LedGroup<'a, C, N> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now);
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+//! Ready-made [`LedEffect`]s for the common UI signals
+//!
+//! Each preset is a plain [`LedEffect`], so it can be tweaked further (e.g. inverted
+//! or limited by a duration) before being set on the LED. The presets are functions
+//! rather than constants, as the effects are generic over the [`Clock`].
+
+use super::effects::{self, LedEffect};
+use crate::time::{Clock, Milliseconds};
+
+/// Fast blink at 4 Hz signalling an error, runs until cleared
+pub fn error_blink<C: Clock>() -> LedEffect<C> {
+    LedEffect::new(effects::blink_period::<C>(C::T::from(250)))
+}
+
+/// Slow breathing ramp repeating every 1.5 seconds, e.g. while pairing,
+/// runs until cleared
+///
+/// LEDs without dimming blink at the same period instead
+pub fn pairing_pulse<C: Clock>() -> LedEffect<C> {
+    LedEffect::new(effects::ramp::<C>(0, u8::MAX, C::T::from(1_500), false))
+}
+
+/// Short 100 ms flash every 3 seconds signalling a low battery, runs until cleared
+pub fn low_battery<C: Clock>() -> LedEffect<C> {
+    LedEffect::new(effects::strobe::<C>(1, C::T::from(100), C::T::from(2_900)))
+}
+
+/// Slow blink at 1 Hz signalling an ongoing operation, runs until cleared
+pub fn busy<C: Clock>() -> LedEffect<C> {
+    LedEffect::new(effects::blink_period::<C>(C::T::from(1_000)))
+}
+
+/// Three quick 80 ms flashes confirming a successful action
+///
+/// The effect is over after the flashes
+pub fn success<C: Clock>() -> LedEffect<C> {
+    let mut fx = LedEffect::new(effects::strobe::<C>(3, C::T::from(80), C::T::from(200)));
+    // the burst of 3 flashes and 2 pauses in between
+    fx.set_duration(Milliseconds::<C::T>::new(C::T::from(400)));
+    fx
+}
+
+/// Single 50 ms blip acknowledging an input
+///
+/// See [`PinLed::overlay_pulse`](super::PinLed::overlay_pulse) for acknowledging
+/// within an ongoing effect
+pub fn acknowledge<C: Clock>() -> LedEffect<C> {
+    LedEffect::new(effects::pulse::<C>(C::T::from(50)))
+}
+
+/// Length of a single flash of the [`reset_reason`] code
+const RESET_FLASH_MS: u32 = 250;
+/// Pause between the repetitions of the [`reset_reason`] code
+const RESET_GAP_MS: u32 = 1_500;
+/// Number of times the [`reset_reason`] code is flashed
+const RESET_REPEATS: u32 = 3;
+
+/// Cause of the last device reset, flashed by the [`reset_reason`] preset
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResetReason {
+    /// Regular power up, 1 flash
+    PowerOn,
+    /// Reset pin has been asserted, 2 flashes
+    External,
+    /// Reset requested by the firmware, 3 flashes
+    Software,
+    /// Watchdog timer has expired, 4 flashes
+    Watchdog,
+    /// Supply voltage has dropped below the brownout threshold, 5 flashes
+    Brownout,
+    /// Cause couldn't be determined, 6 flashes
+    Unknown,
+}
+
+impl ResetReason {
+    /// Returns the number of flashes of the reset code
+    pub fn flash_count(&self) -> u8 {
+        match self {
+            ResetReason::PowerOn => 1,
+            ResetReason::External => 2,
+            ResetReason::Software => 3,
+            ResetReason::Watchdog => 4,
+            ResetReason::Brownout => 5,
+            ResetReason::Unknown => 6,
+        }
+    }
+}
+
+/// Reset code flashed on boot, telling apart the causes of the device reset
+///
+/// The code is a burst of 250 ms flashes, one for a regular power up and more
+/// for the abnormal resets, see [`ResetReason::flash_count`]. The burst is
+/// repeated three times 1.5 seconds apart, so a missed first burst can still be
+/// counted. The effect is over after the last burst.
+pub fn reset_reason<C: Clock>(reason: ResetReason) -> LedEffect<C> {
+    let count = reason.flash_count();
+    let mut fx = LedEffect::new(effects::strobe::<C>(
+        count,
+        C::T::from(RESET_FLASH_MS),
+        C::T::from(RESET_GAP_MS),
+    ));
+    // the repeated bursts and the pauses in between, without the trailing pause
+    let burst = (u32::from(count) * 2 - 1) * RESET_FLASH_MS;
+    let duration = RESET_REPEATS * burst + (RESET_REPEATS - 1) * RESET_GAP_MS;
+    fx.set_duration(Milliseconds::<C::T>::new(C::T::from(duration)));
+    fx
+}
@@ -0,0 +1,391 @@
+use embedded_hal::pwm::SetDutyCycle;
+
+use super::effects::{self, LedEffect};
+use super::{gamma, CycleBoundary, Dimmable, EffectAction, EffectEngine, Led};
+use crate::clock::FrameClock;
+use crate::poll::{Poll, PollError};
+use crate::time::{self, Clock, Instant, Milliseconds};
+
+/// LED driven by a PWM channel
+///
+/// Works the same way as [`PinLed`](super::PinLed), but the "on" state is rendered
+/// at a configurable brightness. Every duty cycle written to the hardware is
+/// passed through the [`gamma`] correction table.
+///
+/// Effects can be chained, see [chain_effect](#method.chain_effect), which together
+/// with the [`EffectType::Fade`](effects::EffectType::Fade) allows smooth transitions
+/// between the LED states.
+pub struct PwmLed<P: SetDutyCycle, C: Clock> {
+    pin: P,
+    engine: EffectEngine<C>,
+    next_effect: Option<effects::LedEffect<C>>,
+    is_on: bool,
+    brightness: u8,
+    held_level: Option<u8>,
+    level: u8,
+    fade_on_clear: Option<Milliseconds<C::T>>,
+    synthetic_now: Instant<C>,
+}
+
+impl<P: SetDutyCycle, C: Clock> PwmLed<P, C> {
+    /// Create new [`PwmLed`] instance for the passed in PWM channel
+    ///
+    /// The LED brightness defaults to full brightness
+    pub fn new(pin: P) -> Self {
+        Self {
+            pin,
+            engine: EffectEngine::new(),
+            next_effect: None,
+            is_on: false,
+            brightness: u8::MAX,
+            held_level: None,
+            level: 0,
+            fade_on_clear: None,
+            synthetic_now: Instant::<C>::new(C::T::from(0)),
+        }
+    }
+
+    /// Sets the logical brightness level used when the LED is on
+    ///
+    /// Unlike [`Dimmable::set_brightness`] leaves the on/off state as is.
+    /// The level is gamma corrected before being translated to the duty cycle.
+    /// The change is applied to the hardware on the next [poll](Led::poll) call.
+    pub fn set_on_level(&mut self, level: u8) {
+        self.brightness = level;
+        self.held_level = None;
+    }
+
+    /// Returns the logical brightness level used when the LED is on
+    pub fn on_level(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Fades the LED out when the effect is cleared
+    ///
+    /// See [set_fade_on_clear](#method.set_fade_on_clear)
+    pub fn with_fade_on_clear(mut self, duration: Milliseconds<C::T>) -> Self {
+        self.set_fade_on_clear(Some(duration));
+        self
+    }
+
+    /// Sets the duration of the fade-out run when the effect is cleared
+    ///
+    /// [`Led::clear_effect`] replaces the running effect with a transient
+    /// [fade_out](#method.fade_out) from the current level instead of snapping
+    /// the LED off. Clearing the effect again during the fade-out turns the LED
+    /// off right away.
+    ///
+    /// Passing [`None`] (default) turns the LED off right away.
+    pub fn set_fade_on_clear(&mut self, duration: Option<Milliseconds<C::T>>) {
+        self.fade_on_clear = duration;
+    }
+
+    /// Returns the duration of the fade-out run when the effect is cleared
+    pub fn fade_on_clear(&self) -> Option<Milliseconds<C::T>> {
+        self.fade_on_clear
+    }
+
+    /// Queues the effect to take place once the current effect completes
+    ///
+    /// The effect is set right away if no effect is in place. Replaces
+    /// the previously chained effect. Clearing the current effect drops
+    /// the chained one as well.
+    pub fn chain_effect(&mut self, effect: LedEffect<C>) {
+        if !self.engine.is_active() {
+            self.engine.set_effect(effect);
+        } else {
+            self.next_effect = Some(effect);
+        }
+    }
+
+    /// Fades from the current level to the brightness level, then runs the `effect`
+    pub fn fade_into(&mut self, effect: LedEffect<C>, duration: Milliseconds<C::T>) {
+        self.set_effect(LedEffect::new(effects::EffectType::Fade {
+            from: self.level,
+            to: self.brightness,
+            duration,
+        }));
+        self.chain_effect(effect);
+    }
+
+    /// Swaps the type of the current effect without restarting it
+    ///
+    /// See [`LedEffect::set_type`](LedEffect::set_type). Does nothing if
+    /// no effect is in place.
+    pub fn update_effect_type(&mut self, fx_type: effects::EffectType<C::T>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.set_type(fx_type);
+        }
+    }
+
+    /// Fades from the current level down to off, replacing the current effect
+    pub fn fade_out(&mut self, duration: Milliseconds<C::T>) {
+        self.set_effect(LedEffect::new(effects::EffectType::Fade {
+            from: self.level,
+            to: 0,
+            duration,
+        }));
+        self.next_effect = None;
+    }
+
+    /// Schedules the effect to replace the current one once the clock reaches `at`
+    ///
+    /// See [`PinLed::schedule_effect`](super::PinLed::schedule_effect)
+    pub fn schedule_effect(&mut self, effect: LedEffect<C>, at: Instant<C>) {
+        self.engine.schedule_effect(effect, at);
+    }
+
+    /// Cancels the scheduled effect and returns it
+    pub fn cancel_scheduled_effect(&mut self) -> Option<LedEffect<C>> {
+        self.engine.cancel_scheduled()
+    }
+
+    /// Polls the LED advancing its own synthetic clock by the `elapsed` time
+    ///
+    /// See [`PinLed::poll_delta`](super::PinLed::poll_delta)
+    pub fn poll_delta(&mut self, elapsed: Milliseconds<C::T>) -> Option<CycleBoundary> {
+        self.synthetic_now = time::advance(self.synthetic_now, elapsed);
+        self.poll(self.synthetic_now)
+    }
+
+    /// Returns the PWM channel
+    pub(super) fn channel(&self) -> &P {
+        &self.pin
+    }
+
+    /// Finishes the current effect, moving on to the chained one
+    fn finish_effect(&mut self) {
+        if let Some(next) = self.next_effect.take() {
+            self.engine.set_effect(next);
+        }
+        // the level is written by the poll finishing the effect
+        self.is_on = false;
+    }
+
+    /// Writes the level of the LED without an effect right away
+    ///
+    /// Skipped while an effect is in place, the poll reconciles the level in such case
+    fn write_idle_level(&mut self) {
+        if self.engine.is_active() {
+            return;
+        }
+        let level = if self.is_on { self.lit_level() } else { 0 };
+        self.write_level(level);
+    }
+
+    /// Returns the level of the LED when on, a finished fade holds its target level
+    fn lit_level(&self) -> u8 {
+        self.held_level.unwrap_or(self.brightness)
+    }
+
+    /// Writes the logical brightness level to the PWM channel
+    fn write_level(&mut self, level: u8) {
+        self.level = level;
+        self.pin
+            .set_duty_cycle_fraction(gamma::correct(level), u16::MAX)
+            .unwrap();
+    }
+}
+
+impl<P: SetDutyCycle> PwmLed<P, FrameClock> {
+    /// Polls the LED at the `frame` of a fixed rate render loop
+    ///
+    /// See [`PinLed::poll_frame`](super::PinLed::poll_frame)
+    pub fn poll_frame(&mut self, frame: u32) -> Option<CycleBoundary> {
+        self.poll(FrameClock::frame(frame))
+    }
+}
+
+impl<P: SetDutyCycle, C: Clock> Dimmable for PwmLed<P, C> {
+    /// Turns the LED on at the `level`, level `0` turns the LED off
+    /// leaving the brightness level as is
+    fn set_brightness(&mut self, level: u8) {
+        if level == 0 {
+            self.turn_off();
+            return;
+        }
+        self.set_on_level(level);
+        self.turn_on();
+    }
+
+    fn brightness(&self) -> u8 {
+        match self.is_on {
+            true => self.lit_level(),
+            false => 0,
+        }
+    }
+}
+
+impl<P: SetDutyCycle, C: Clock> Led<C> for PwmLed<P, C> {
+    fn is_on(&self) -> bool {
+        self.is_on
+    }
+
+    fn turn_on(&mut self) {
+        self.is_on = true;
+        self.held_level = None;
+        self.write_idle_level();
+    }
+
+    fn turn_off(&mut self) {
+        self.is_on = false;
+        self.held_level = None;
+        self.write_idle_level();
+    }
+
+    fn toggle(&mut self) {
+        self.is_on = !self.is_on;
+        self.held_level = None;
+        self.write_idle_level();
+    }
+
+    fn set_effect(&mut self, effect: effects::LedEffect<C>) {
+        self.engine.set_effect(effect);
+    }
+
+    fn set_effect_duration(&mut self, dur: Milliseconds<<C as Clock>::T>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.set_duration(dur)
+        }
+    }
+
+    fn set_effect_phase_offset(&mut self, offset: Milliseconds<C::T>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.set_phase_offset(offset)
+        }
+    }
+
+    fn get_effect(&self) -> Option<&LedEffect<C>> {
+        self.engine.effect()
+    }
+
+    fn clear_effect(&mut self) {
+        let is_fading_out = self
+            .engine
+            .effect()
+            .is_some_and(|fx| matches!(fx.get_type(), effects::EffectType::Fade { to: 0, .. }));
+        if let Some(duration) = self.fade_on_clear {
+            if self.engine.is_active() && !is_fading_out && self.level > 0 {
+                self.fade_out(duration);
+                self.is_on = false;
+                return;
+            }
+        }
+
+        self.engine.take_effect();
+        self.next_effect = None;
+        self.turn_off();
+    }
+
+    fn pause_effect(&mut self, now: Instant<C>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.pause(now)
+        }
+    }
+
+    fn resume_effect(&mut self, now: Instant<C>) {
+        if let Some(fx) = self.engine.effect_mut() {
+            fx.resume(now)
+        }
+    }
+
+    fn poll(&mut self, now: Instant<C>) -> Option<CycleBoundary> {
+        self.engine.arm_scheduled(now);
+        let Some(fx) = self.engine.effect() else {
+            // No effect on led, proceed as normal
+            self.write_idle_level();
+            return None;
+        };
+
+        // Paused effect keeps the LED as is
+        if fx.is_paused() {
+            return None;
+        }
+
+        let level = match self.engine.poll(now) {
+            Some(EffectAction::Completed { is_on, level }) => {
+                // effect is over, fades hold the target level
+                self.finish_effect();
+                self.is_on = is_on;
+                self.held_level = level;
+                if is_on {
+                    self.lit_level()
+                } else {
+                    0
+                }
+            }
+            Some(EffectAction::SetDuty(level)) => level,
+            _ if self.engine.is_on() => self.brightness,
+            _ => 0,
+        };
+        self.write_level(level);
+        self.engine.cycle_boundary()
+    }
+}
+
+impl<P: SetDutyCycle, C: Clock> Poll<C> for PwmLed<P, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        Led::poll(self, now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PwmLed;
+    use crate::led::effects::{EffectType, LedEffect};
+    use crate::led::{gamma, Dimmable, Led};
+    use crate::mock::{MockClock, MockPin, MockPwmPin};
+    use crate::time::Milliseconds;
+
+    type TestLed<'a> = PwmLed<MockPwmPin<'a>, MockClock>;
+
+    #[test]
+    fn on_level_leaves_the_led_state_as_is() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.pwm());
+
+        led.set_on_level(100);
+        assert!(!led.is_on());
+        assert_eq!(Dimmable::brightness(&led), 0);
+
+        Dimmable::set_brightness(&mut led, 100);
+        led.poll(clock.now());
+        assert!(led.is_on());
+        assert_eq!(Dimmable::brightness(&led), 100);
+        assert_eq!(pin.duty(), gamma::correct(100));
+
+        Dimmable::set_brightness(&mut led, 0);
+        assert!(!led.is_on());
+        assert_eq!(led.on_level(), 100);
+    }
+
+    #[test]
+    fn finished_fade_keeps_the_on_level() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut led = TestLed::new(pin.pwm());
+        led.set_on_level(200);
+
+        led.set_effect(LedEffect::new(EffectType::Fade {
+            from: 0,
+            to: 50,
+            duration: Milliseconds(100),
+        }));
+        led.poll(clock.now());
+        clock.advance(101);
+        led.poll(clock.now());
+
+        assert!(led.is_on());
+        assert_eq!(Dimmable::brightness(&led), 50);
+        assert_eq!(pin.duty(), gamma::correct(50));
+        assert_eq!(led.on_level(), 200);
+
+        led.turn_off();
+        led.turn_on();
+        led.poll(clock.now());
+        assert_eq!(Dimmable::brightness(&led), 200);
+        assert_eq!(pin.duty(), gamma::correct(200));
+    }
+}
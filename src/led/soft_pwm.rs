@@ -0,0 +1,252 @@
+use core::convert::Infallible;
+use embedded_hal::digital::{OutputPin, PinState};
+use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+use num_traits::{Bounded, CheckedMul};
+
+use super::effects::{EffectType, LedEffect};
+use super::{CycleBoundary, Dimmable, Led, PwmLed};
+use crate::poll::{Poll, PollError};
+use crate::time::{self, Clock, Instant, Milliseconds};
+
+/// Duty cycle written by the [`PwmLed`] driving the [`SoftPwmLed`]
+struct DutyLatch {
+    duty: u16,
+}
+
+impl ErrorType for DutyLatch {
+    type Error = Infallible;
+}
+
+impl SetDutyCycle for DutyLatch {
+    fn max_duty_cycle(&self) -> u16 {
+        u16::MAX
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        self.duty = duty;
+        Ok(())
+    }
+}
+
+/// LED dimmed by a software (bit-banged) PWM on an [`OutputPin`] of `embedded_hal`
+///
+/// Works the same way as [`PwmLed`], including the gamma correction and the effect
+/// chaining, for the pins without a timer channel. Each poll drives the pin on or off
+/// depending on where `now` falls within the PWM period and on the current duty cycle.
+///
+/// The PWM is resolved only at the poll granularity, the LED has to be polled
+/// at least `levels * frequency` times per second in order to render `levels` distinct
+/// brightness levels at the PWM `frequency`. E.g. 16 levels at the default 100 Hz
+/// require polling at 1.6 kHz, with a clock ticking at least as fast. Lower poll rates
+/// still work, but the brightness levels are coarser and the LED may flicker.
+///
+/// Unlike the other LEDs, [`Led::turn_on`] and [`Led::turn_off`] do not drive the pin
+/// right away, since the pin state depends on the PWM phase. The next poll applies them.
+pub struct SoftPwmLed<P: OutputPin, C: Clock> {
+    pin: P,
+    pin_state: Option<PinState>,
+    led: PwmLed<DutyLatch, C>,
+    period: Milliseconds<C::T>,
+}
+
+impl<P: OutputPin, C: Clock> SoftPwmLed<P, C> {
+    /// Create new [`SoftPwmLed`] instance for the passed in `pin`
+    ///
+    /// The PWM period defaults to 10 ms (100 Hz)
+    pub fn new(pin: P) -> Self {
+        Self {
+            pin,
+            pin_state: None,
+            led: PwmLed::new(DutyLatch { duty: 0 }),
+            period: Milliseconds::<C::T>::new(C::T::from(10)),
+        }
+    }
+
+    /// Sets the PWM period
+    ///
+    /// See [set_period](#method.set_period)
+    pub fn with_period(mut self, period: Milliseconds<C::T>) -> Self {
+        self.set_period(period);
+        self
+    }
+
+    /// Sets the PWM period
+    ///
+    /// Shorter periods reduce the flicker but require faster polls for the same
+    /// resolution, see [`SoftPwmLed`]. Zero period renders any non-zero duty cycle
+    /// as fully on.
+    pub fn set_period(&mut self, period: Milliseconds<C::T>) {
+        self.period = period;
+    }
+
+    /// Returns the PWM period
+    pub fn period(&self) -> Milliseconds<C::T> {
+        self.period
+    }
+
+    /// Sets the logical brightness level used when the LED is on
+    ///
+    /// See [`PwmLed::set_on_level`]
+    pub fn set_on_level(&mut self, level: u8) {
+        self.led.set_on_level(level);
+    }
+
+    /// Returns the logical brightness level used when the LED is on
+    pub fn on_level(&self) -> u8 {
+        self.led.on_level()
+    }
+
+    /// Fades the LED out when the effect is cleared
+    ///
+    /// See [`PwmLed::set_fade_on_clear`]
+    pub fn with_fade_on_clear(mut self, duration: Milliseconds<C::T>) -> Self {
+        self.set_fade_on_clear(Some(duration));
+        self
+    }
+
+    /// Sets the duration of the fade-out run when the effect is cleared
+    ///
+    /// See [`PwmLed::set_fade_on_clear`]
+    pub fn set_fade_on_clear(&mut self, duration: Option<Milliseconds<C::T>>) {
+        self.led.set_fade_on_clear(duration);
+    }
+
+    /// Queues the effect to take place once the current effect completes
+    ///
+    /// See [`PwmLed::chain_effect`]
+    pub fn chain_effect(&mut self, effect: LedEffect<C>) {
+        self.led.chain_effect(effect);
+    }
+
+    /// Fades from the current level to the brightness level, then runs the `effect`
+    pub fn fade_into(&mut self, effect: LedEffect<C>, duration: Milliseconds<C::T>) {
+        self.led.fade_into(effect, duration);
+    }
+
+    /// Swaps the type of the current effect without restarting it
+    ///
+    /// See [`PwmLed::update_effect_type`]
+    pub fn update_effect_type(&mut self, fx_type: EffectType<C::T>) {
+        self.led.update_effect_type(fx_type);
+    }
+
+    /// Schedules the effect to replace the current one once the clock reaches `at`
+    ///
+    /// See [`PwmLed::schedule_effect`]
+    pub fn schedule_effect(&mut self, effect: LedEffect<C>, at: Instant<C>) {
+        self.led.schedule_effect(effect, at);
+    }
+
+    /// Cancels the scheduled effect and returns it
+    pub fn cancel_scheduled_effect(&mut self) -> Option<LedEffect<C>> {
+        self.led.cancel_scheduled_effect()
+    }
+
+    /// Fades from the current level down to off, replacing the current effect
+    pub fn fade_out(&mut self, duration: Milliseconds<C::T>) {
+        self.led.fade_out(duration);
+    }
+
+    /// Releases the LED pin
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+
+    /// Indicates that the pin should be on at `now` for the current duty cycle
+    fn is_pin_on(&self, now: Instant<C>) -> bool {
+        let duty = self.led.channel().duty;
+        if duty == 0 {
+            return false;
+        }
+
+        // period in the clock ticks, measured as an offset from the epoch
+        let period = Instant::<C>::new(C::T::from(0))
+            .checked_add(self.period)
+            .map_or(C::T::max_value(), time::ticks);
+        if period == C::T::from(0) {
+            return true;
+        }
+
+        let duty = C::T::from(u32::from(duty));
+        let max = C::T::from(u32::from(u16::MAX));
+        let on_ticks = match period.checked_mul(&duty) {
+            Some(scaled) => scaled / max,
+            None => (period / max) * duty,
+        };
+        time::ticks(now) % period < on_ticks
+    }
+}
+
+impl<P: OutputPin, C: Clock> Dimmable for SoftPwmLed<P, C> {
+    fn set_brightness(&mut self, level: u8) {
+        Dimmable::set_brightness(&mut self.led, level);
+    }
+
+    fn brightness(&self) -> u8 {
+        Dimmable::brightness(&self.led)
+    }
+}
+
+impl<P: OutputPin, C: Clock> Led<C> for SoftPwmLed<P, C> {
+    fn is_on(&self) -> bool {
+        self.led.is_on()
+    }
+
+    fn turn_on(&mut self) {
+        self.led.turn_on();
+    }
+
+    fn turn_off(&mut self) {
+        self.led.turn_off();
+    }
+
+    fn toggle(&mut self) {
+        self.led.toggle();
+    }
+
+    fn set_effect(&mut self, effect: LedEffect<C>) {
+        self.led.set_effect(effect);
+    }
+
+    fn set_effect_duration(&mut self, dur: Milliseconds<C::T>) {
+        self.led.set_effect_duration(dur);
+    }
+
+    fn set_effect_phase_offset(&mut self, offset: Milliseconds<C::T>) {
+        self.led.set_effect_phase_offset(offset);
+    }
+
+    fn get_effect(&self) -> Option<&LedEffect<C>> {
+        self.led.get_effect()
+    }
+
+    fn clear_effect(&mut self) {
+        self.led.clear_effect();
+    }
+
+    fn pause_effect(&mut self, now: Instant<C>) {
+        self.led.pause_effect(now);
+    }
+
+    fn resume_effect(&mut self, now: Instant<C>) {
+        self.led.resume_effect(now);
+    }
+
+    fn poll(&mut self, now: Instant<C>) -> Option<CycleBoundary> {
+        let boundary = self.led.poll(now);
+
+        let state = PinState::from(self.is_pin_on(now));
+        if self.pin_state != Some(state) {
+            self.pin.set_state(state).unwrap();
+            self.pin_state = Some(state);
+        }
+        boundary
+    }
+}
+
+impl<P: OutputPin, C: Clock> Poll<C> for SoftPwmLed<P, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        Led::poll(self, now);
+        Ok(())
+    }
+}
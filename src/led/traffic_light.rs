@@ -0,0 +1,192 @@
+use num_traits::CheckedMul;
+
+use super::Led;
+use crate::poll::{Poll, PollError};
+use crate::time::{self, Clock, FixedPoint, Instant, Milliseconds};
+
+/// Default half period of the [`TrafficLightState::AmberBlink`]
+const DEFAULT_BLINK_INTERVAL_MS: u32 = 500;
+
+/// State shown by the [`TrafficLight`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrafficLightState {
+    /// All LEDs are off
+    #[default]
+    Off,
+    /// Only the red LED is lit
+    Red,
+    /// Only the amber LED is lit
+    Amber,
+    /// Only the green LED is lit
+    Green,
+    /// Only the amber LED is blinking, e.g. while transitioning between the states
+    AmberBlink,
+}
+
+/// Red / amber / green status indicator made of three LEDs
+///
+/// At most a single LED is lit at any time. The LEDs are driven solely by the
+/// indicator, [poll](#method.poll) turns them on and off according to the
+/// [`TrafficLightState`] overriding any change done to them directly.
+pub struct TrafficLight<R: Led<C>, A: Led<C>, G: Led<C>, C: Clock> {
+    red: R,
+    amber: A,
+    green: G,
+    state: TrafficLightState,
+    blink_interval: Milliseconds<C::T>,
+    blink_started_at: Option<Instant<C>>,
+}
+
+impl<R: Led<C>, A: Led<C>, G: Led<C>, C: Clock> TrafficLight<R, A, G, C> {
+    /// Create new [`TrafficLight`] from the passed in LEDs, showing
+    /// [`TrafficLightState::Off`]
+    ///
+    /// The effects of the LEDs are cleared
+    pub fn new(red: R, amber: A, green: G) -> Self {
+        let mut light = Self {
+            red,
+            amber,
+            green,
+            state: TrafficLightState::Off,
+            blink_interval: Milliseconds::<C::T>::new(C::T::from(DEFAULT_BLINK_INTERVAL_MS)),
+            blink_started_at: None,
+        };
+        light.red.clear_effect();
+        light.amber.clear_effect();
+        light.green.clear_effect();
+        light
+    }
+
+    /// Sets the blink interval of the [`TrafficLightState::AmberBlink`]
+    ///
+    /// See [set_blink_interval](#method.set_blink_interval)
+    pub fn with_blink_interval(mut self, interval: Milliseconds<C::T>) -> Self {
+        self.set_blink_interval(interval);
+        self
+    }
+
+    /// Sets the blink interval of the [`TrafficLightState::AmberBlink`]
+    ///
+    /// The amber LED toggles every `interval`, 500 ms by default. Zero interval
+    /// is treated as one millisecond.
+    pub fn set_blink_interval(&mut self, interval: Milliseconds<C::T>) {
+        self.blink_interval = if interval.integer() == C::T::from(0) {
+            Milliseconds::<C::T>::new(C::T::from(1))
+        } else {
+            interval
+        };
+    }
+
+    /// Sets the state shown by the indicator
+    ///
+    /// The LEDs are switched on the next poll. The blink starts in the lit half
+    /// once the [`TrafficLightState::AmberBlink`] is entered, setting the same
+    /// state again doesn't restart it.
+    pub fn set_state(&mut self, state: TrafficLightState) {
+        if self.state != state {
+            self.blink_started_at = None;
+        }
+        self.state = state;
+    }
+
+    /// Returns the state shown by the indicator
+    pub fn state(&self) -> TrafficLightState {
+        self.state
+    }
+
+    /// Releases the red, amber and green LEDs
+    pub fn into_inner(self) -> (R, A, G) {
+        (self.red, self.amber, self.green)
+    }
+
+    /// Polls the indicator, switching the LEDs to the current state and polling them
+    pub fn poll(&mut self, now: Instant<C>) {
+        let (red, amber, green) = match self.state {
+            TrafficLightState::Off => (false, false, false),
+            TrafficLightState::Red => (true, false, false),
+            TrafficLightState::Amber => (false, true, false),
+            TrafficLightState::Green => (false, false, true),
+            TrafficLightState::AmberBlink => (false, self.is_blink_lit(now), false),
+        };
+
+        // LEDs are turned off first, so two of them are never lit at once
+        Self::switch(&mut self.red, red, false);
+        Self::switch(&mut self.amber, amber, false);
+        Self::switch(&mut self.green, green, false);
+        Self::switch(&mut self.red, red, true);
+        Self::switch(&mut self.amber, amber, true);
+        Self::switch(&mut self.green, green, true);
+
+        self.red.poll(now);
+        self.amber.poll(now);
+        self.green.poll(now);
+    }
+
+    /// Indicates that the amber LED is in the lit half of the blink at `now`
+    fn is_blink_lit(&mut self, now: Instant<C>) -> bool {
+        // the blink is re-anchored every period, so the elapsed time never saturates
+        let period = self
+            .blink_interval
+            .integer()
+            .checked_mul(&C::T::from(2))
+            .unwrap_or(C::T::from(0));
+        let started_at = self.blink_started_at.get_or_insert(now);
+        let elapsed = time::realign(started_at, now, Milliseconds::<C::T>::new(period));
+        let toggles = elapsed.integer() / self.blink_interval.integer();
+        toggles % C::T::from(2) == C::T::from(0)
+    }
+
+    /// Switches the LED to `is_on`, provided it matches the `pass`
+    fn switch<L: Led<C>>(led: &mut L, is_on: bool, pass: bool) {
+        if is_on == pass && led.is_on() != is_on {
+            if is_on {
+                led.turn_on();
+            } else {
+                led.turn_off();
+            }
+        }
+    }
+}
+
+impl<R: Led<C>, A: Led<C>, G: Led<C>, C: Clock> Poll<C> for TrafficLight<R, A, G, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TrafficLight, TrafficLightState};
+    use crate::led::{OffState, PinLed};
+    use crate::mock::{MockClock, MockOutputPin, MockPin};
+
+    type MicrosClock = MockClock<u32, 1_000_000>;
+    type TestLed<'a> = PinLed<MockOutputPin<'a>, MicrosClock>;
+
+    #[test]
+    fn keeps_blinking_past_half_of_the_clock_range() {
+        let clock = MicrosClock::new();
+        let (red, amber, green) = (MockPin::new(), MockPin::new(), MockPin::new());
+        let led = |pin| TestLed::new(pin).with_off_state(OffState::DriveLow);
+        let mut light =
+            TrafficLight::new(led(red.output()), led(amber.output()), led(green.output()));
+        light.set_state(TrafficLightState::AmberBlink);
+
+        // ~42 minutes of a microsecond clock, past half of its range
+        for _ in 0..250 {
+            light.poll(clock.now());
+            clock.advance(10_000_000);
+        }
+
+        // the blink started in the lit half, whole seconds later it's lit again
+        clock.advance(250_000);
+        light.poll(clock.now());
+        assert!(amber.is_high());
+        clock.advance(500_000);
+        light.poll(clock.now());
+        assert!(!amber.is_high());
+    }
+}
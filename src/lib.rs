@@ -1,5 +1,24 @@
+//! Simple UI elements (LED, hardware switch, etc.) on top of `embedded-hal`
+//!
+//! The time types come from the `embedded-time` crate with the default `embedded-time`
+//! feature and from a built-in backend without it. The feature is **not additive**, it
+//! replaces the public [`Clock`](time::Clock), [`Instant`](time::Instant) and
+//! [`Milliseconds`](time::Milliseconds) types, see [`time`](time#feature-unification).
+
 #![cfg_attr(not(any(test, feature = "std")), no_std)]
 #![deny(unsafe_code)]
 
+pub mod analog;
+pub mod button;
+pub mod buzzer;
+pub mod clock;
+pub mod encoder;
+pub mod keypad;
+pub mod knob;
 pub mod led;
-pub mod switch;
\ No newline at end of file
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod poll;
+pub mod prelude;
+pub mod switch;
+pub mod time;
@@ -0,0 +1,251 @@
+//! Mock clock and pins for deterministic testing of the UI elements
+//!
+//! The mocks don't allocate and work in `no_std`, so they can be used both
+//! in host unit tests and in on-target tests.
+
+use core::cell::Cell;
+use core::hash::Hash;
+
+use embedded_hal::digital::{self, InputPin, OutputPin, StatefulOutputPin};
+use embedded_hal::pwm::{self, SetDutyCycle};
+
+use crate::time::{Clock, ClockError, Fraction, Instant, TimeInt};
+
+/// Clock with programmatically controlled time
+///
+/// The clock starts at zero and only moves when told to. It ticks in milliseconds
+/// by default, `HZ` sets the tick rate, e.g. `MockClock<u64, 1_000_000>` mimics
+/// the 64-bit microsecond counters of the HALs.
+#[derive(Clone, Debug, Default)]
+pub struct MockClock<T: TimeInt + Hash = u32, const HZ: u32 = 1_000> {
+    now: Cell<T>,
+    is_failing: Cell<bool>,
+}
+
+impl<T: TimeInt + Hash, const HZ: u32> MockClock<T, HZ> {
+    /// Create new [`MockClock`] starting at zero
+    pub fn new() -> Self {
+        Self {
+            now: Cell::new(T::from(0)),
+            is_failing: Cell::new(false),
+        }
+    }
+
+    /// Sets the current time in ticks, milliseconds by default
+    pub fn set(&self, ticks: T) {
+        self.now.set(ticks);
+    }
+
+    /// Moves the current time forward by `ticks`, milliseconds by default
+    ///
+    /// Wraps around the same way as a hardware counter would
+    pub fn advance(&self, ticks: T) {
+        self.now.set(self.now.get().wrapping_add(&ticks));
+    }
+
+    /// Returns the current instant
+    pub fn now(&self) -> Instant<Self> {
+        Instant::new(self.now.get())
+    }
+
+    /// Makes all reads through the [`Clock`] trait fail with [`ClockError::Unspecified`]
+    pub fn set_failing(&self, is_failing: bool) {
+        self.is_failing.set(is_failing);
+    }
+}
+
+impl<T: TimeInt + Hash, const HZ: u32> Clock for MockClock<T, HZ> {
+    type T = T;
+
+    const SCALING_FACTOR: Fraction = Fraction::new(1, HZ);
+
+    fn try_now(&self) -> Result<Instant<Self>, ClockError> {
+        if self.is_failing.get() {
+            return Err(ClockError::Unspecified);
+        }
+        Ok(self.now())
+    }
+}
+
+/// Error reported by the mock pins when a failure is injected
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MockError;
+
+impl digital::Error for MockError {
+    fn kind(&self) -> digital::ErrorKind {
+        digital::ErrorKind::Other
+    }
+}
+
+impl pwm::Error for MockError {
+    fn kind(&self) -> pwm::ErrorKind {
+        pwm::ErrorKind::Other
+    }
+}
+
+/// Shared state of a mock pin
+///
+/// The pin handles borrow the state, so the test keeps access to it
+/// while the pin is owned by the tested UI element.
+#[derive(Debug, Default)]
+pub struct MockPin {
+    is_high: Cell<bool>,
+    duty: Cell<u16>,
+    writes: Cell<u32>,
+    reads: Cell<u32>,
+    is_failing: Cell<bool>,
+}
+
+impl MockPin {
+    /// Create new [`MockPin`] in the low state
+    pub const fn new() -> Self {
+        Self {
+            is_high: Cell::new(false),
+            duty: Cell::new(0),
+            writes: Cell::new(0),
+            reads: Cell::new(0),
+            is_failing: Cell::new(false),
+        }
+    }
+
+    /// Returns an output pin handle driving this pin
+    pub fn output(&self) -> MockOutputPin<'_> {
+        MockOutputPin { pin: self }
+    }
+
+    /// Returns an input pin handle reading this pin
+    pub fn input(&self) -> MockInputPin<'_> {
+        MockInputPin { pin: self }
+    }
+
+    /// Returns a PWM channel handle driving this pin
+    pub fn pwm(&self) -> MockPwmPin<'_> {
+        MockPwmPin { pin: self }
+    }
+
+    /// Indicates that the pin is high
+    pub fn is_high(&self) -> bool {
+        self.is_high.get()
+    }
+
+    /// Sets the pin level read by the input handles
+    pub fn set_high(&self, is_high: bool) {
+        self.is_high.set(is_high);
+    }
+
+    /// Returns the duty cycle last written by the PWM handles
+    pub fn duty(&self) -> u16 {
+        self.duty.get()
+    }
+
+    /// Returns the number of writes issued through the handles
+    pub fn write_count(&self) -> u32 {
+        self.writes.get()
+    }
+
+    /// Returns the number of reads issued through the handles
+    pub fn read_count(&self) -> u32 {
+        self.reads.get()
+    }
+
+    /// Resets the read and write counters
+    pub fn reset_counters(&self) {
+        self.writes.set(0);
+        self.reads.set(0);
+    }
+
+    /// Makes all reads and writes through the handles fail with [`MockError`]
+    pub fn set_failing(&self, is_failing: bool) {
+        self.is_failing.set(is_failing);
+    }
+
+    fn read(&self) -> Result<bool, MockError> {
+        if self.is_failing.get() {
+            return Err(MockError);
+        }
+        self.reads.set(self.reads.get() + 1);
+        Ok(self.is_high.get())
+    }
+
+    fn write(&self, is_high: bool, duty: u16) -> Result<(), MockError> {
+        if self.is_failing.get() {
+            return Err(MockError);
+        }
+        self.writes.set(self.writes.get() + 1);
+        self.is_high.set(is_high);
+        self.duty.set(duty);
+        Ok(())
+    }
+}
+
+/// Output pin handle of a [`MockPin`]
+#[derive(Debug)]
+pub struct MockOutputPin<'a> {
+    pin: &'a MockPin,
+}
+
+impl digital::ErrorType for MockOutputPin<'_> {
+    type Error = MockError;
+}
+
+impl OutputPin for MockOutputPin<'_> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.write(false, 0)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.write(true, u16::MAX)
+    }
+}
+
+impl StatefulOutputPin for MockOutputPin<'_> {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.read()
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.read().map(|is_high| !is_high)
+    }
+}
+
+/// Input pin handle of a [`MockPin`]
+#[derive(Debug)]
+pub struct MockInputPin<'a> {
+    pin: &'a MockPin,
+}
+
+impl digital::ErrorType for MockInputPin<'_> {
+    type Error = MockError;
+}
+
+impl InputPin for MockInputPin<'_> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.read()
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.read().map(|is_high| !is_high)
+    }
+}
+
+/// PWM channel handle of a [`MockPin`]
+///
+/// The maximum duty cycle is [`u16::MAX`], the pin reads high for any non-zero duty
+#[derive(Debug)]
+pub struct MockPwmPin<'a> {
+    pin: &'a MockPin,
+}
+
+impl pwm::ErrorType for MockPwmPin<'_> {
+    type Error = MockError;
+}
+
+impl SetDutyCycle for MockPwmPin<'_> {
+    fn max_duty_cycle(&self) -> u16 {
+        u16::MAX
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        self.pin.write(duty > 0, duty)
+    }
+}
@@ -0,0 +1,331 @@
+//! Polling of heterogeneous UI elements with a single shared instant
+
+use core::marker::PhantomData;
+
+#[cfg(debug_assertions)]
+use crate::time;
+use crate::time::{Clock, ClockError, Instant, Milliseconds};
+
+/// Error reported by the [`Poll::poll_at`] when the hardware couldn't be accessed
+///
+/// Reported as well when the clock couldn't be read by the polls sampling it on their own.
+/// The underlying error is dropped, poll the element directly to inspect it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PollError;
+
+/// UI element advanced by the periodic polls
+///
+/// Shared interface of the LEDs, switches, encoders, etc. so that they can be polled
+/// together, see [`PollGroup`]. The results of the polls (switch events, encoder steps,
+/// etc.) are dropped, the elements are to be queried for their state afterwards.
+pub trait Poll<C: Clock> {
+    /// Polls the element at `now`
+    ///
+    /// Returns an error if the hardware couldn't be accessed, the element state
+    /// is left untouched in such case, so the poll can be simply retried.
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError>;
+}
+
+/// Group of UI elements polled together
+///
+/// Polls every member with the same instant, so the elements don't drift apart
+/// by the time spent polling the preceding ones.
+pub struct PollGroup<'a, C: Clock, const N: usize> {
+    elements: [&'a mut dyn Poll<C>; N],
+    poll_gap: PollGapMonitor<C>,
+}
+
+impl<'a, C: Clock, const N: usize> PollGroup<'a, C, N> {
+    /// Create new [`PollGroup`] from the passed in elements
+    pub fn new(elements: [&'a mut dyn Poll<C>; N]) -> Self {
+        Self {
+            elements,
+            poll_gap: PollGapMonitor::new(),
+        }
+    }
+
+    /// Sets the maximum gap between two successive polls, checked in the debug builds
+    ///
+    /// See [set_max_poll_gap](#method.set_max_poll_gap)
+    pub fn with_max_poll_gap(mut self, max_gap: Milliseconds<C::T>) -> Self {
+        self.set_max_poll_gap(Some(max_gap));
+        self
+    }
+
+    /// Sets the maximum gap between two successive polls, see [`PollGapMonitor::set_max_gap`]
+    pub fn set_max_poll_gap(&mut self, max_gap: Option<Milliseconds<C::T>>) {
+        self.poll_gap.set_max_gap(max_gap);
+    }
+
+    /// Indicates that the group has been polled less often than the maximum poll gap
+    ///
+    /// Latched until cleared by [take_poll_gap_exceeded](#method.take_poll_gap_exceeded).
+    /// Always `false` in the release builds.
+    pub fn poll_gap_exceeded(&self) -> bool {
+        self.poll_gap.has_exceeded()
+    }
+
+    /// Returns the latched exceeded poll gap and clears it
+    pub fn take_poll_gap_exceeded(&mut self) -> bool {
+        self.poll_gap.take_exceeded()
+    }
+
+    /// Polls all elements of the group with the same `now`
+    ///
+    /// A failing element doesn't prevent the following ones from being polled.
+    ///
+    /// Returns an error if any of the elements failed
+    pub fn poll(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll_gap.record(now);
+        let mut result = Ok(());
+        for element in self.elements.iter_mut() {
+            result = result.and(element.poll_at(now));
+        }
+        result
+    }
+
+    /// Polls all elements of the group with a single instant taken from the `clock`
+    ///
+    /// See [poll](#method.poll). Returns an error without polling the elements
+    /// if the clock couldn't be read.
+    pub fn poll_clock(&mut self, clock: &C) -> Result<(), PollError> {
+        let now = clock.try_now().map_err(|_| PollError)?;
+        self.poll(now)
+    }
+}
+
+/// Source of the current instant owned by a [`SelfPolled`] element
+///
+/// Implemented for the clock references, e.g. `&'static C` of a clock placed into
+/// a `static`, and for the plain `fn() -> Instant<C>` providers, e.g. reading
+/// a clock shared through the RTIC resources. The function items are to be cast
+/// to the pointers, e.g. `SelfPolled::new(led, now as fn() -> _)`.
+pub trait InstantSource<C: Clock> {
+    /// Returns the current instant
+    ///
+    /// Returns an error if the underlying clock couldn't be read
+    fn try_now(&self) -> Result<Instant<C>, ClockError>;
+}
+
+impl<C: Clock> InstantSource<C> for &C {
+    fn try_now(&self) -> Result<Instant<C>, ClockError> {
+        Clock::try_now(*self)
+    }
+}
+
+impl<C: Clock> InstantSource<C> for fn() -> Instant<C> {
+    fn try_now(&self) -> Result<Instant<C>, ClockError> {
+        Ok(self())
+    }
+}
+
+/// UI element sampling the time on its own
+///
+/// Pairs the element with an owned [`InstantSource`], so it can be polled by
+/// [poll_self](#method.poll_self) without threading the instant through the
+/// application, e.g. from a task that has no access to the clock.
+///
+/// Each self-polled element samples the time separately, so the elements polled
+/// one after another see slightly different instants and may drift apart, e.g.
+/// two LEDs blinking at the same rate toggle a few ticks apart. Poll the elements
+/// meant to stay in sync with a shared instant instead, either directly or by
+/// a [`PollGroup`], which the [`SelfPolled`] can still be a member of.
+pub struct SelfPolled<E: Poll<C>, S: InstantSource<C>, C: Clock> {
+    element: E,
+    source: S,
+    clock: PhantomData<C>,
+}
+
+impl<E: Poll<C>, S: InstantSource<C>, C: Clock> SelfPolled<E, S, C> {
+    /// Create new [`SelfPolled`] pairing the `element` with the instant `source`
+    pub fn new(element: E, source: S) -> Self {
+        Self {
+            element,
+            source,
+            clock: PhantomData,
+        }
+    }
+
+    /// Returns the current instant of the source
+    ///
+    /// E.g. for polling the element directly to get the results of the poll,
+    /// such as the switch events, see [inner_mut](#method.inner_mut)
+    pub fn try_now(&self) -> Result<Instant<C>, ClockError> {
+        self.source.try_now()
+    }
+
+    /// Polls the element at the current instant of the source
+    ///
+    /// See [`Poll::poll_at`]. Returns an error without polling the element
+    /// if the source couldn't be read.
+    pub fn poll_self(&mut self) -> Result<(), PollError> {
+        let now = self.source.try_now().map_err(|_| PollError)?;
+        self.element.poll_at(now)
+    }
+
+    /// Returns a reference to the element
+    pub fn inner(&self) -> &E {
+        &self.element
+    }
+
+    /// Returns a mutable reference to the element
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.element
+    }
+
+    /// Releases the element and the instant source
+    pub fn into_inner(self) -> (E, S) {
+        (self.element, self.source)
+    }
+}
+
+impl<E: Poll<C>, S: InstantSource<C>, C: Clock> Poll<C> for SelfPolled<E, S, C> {
+    /// Polls the element at the passed in `now`, ignoring the instant source
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.element.poll_at(now)
+    }
+}
+
+/// Debug build diagnostic of the gaps between the successive polls
+///
+/// The elements rely on being polled regularly, polling them too rarely silently
+/// degrades the timing (missed blink toggles, debounced edges, etc.). The monitor
+/// latches a flag once the gap between two polls exceeds the configured maximum,
+/// e.g. to catch a main loop blocking for too long.
+///
+/// Compiled out in the release builds, where the monitor holds no state and
+/// never reports an exceeded gap.
+#[derive(Clone, Debug)]
+pub struct PollGapMonitor<C: Clock> {
+    #[cfg(debug_assertions)]
+    max_gap: Option<Milliseconds<C::T>>,
+    #[cfg(debug_assertions)]
+    last_poll_at: Option<Instant<C>>,
+    #[cfg(debug_assertions)]
+    has_exceeded: bool,
+    #[cfg(not(debug_assertions))]
+    _clock: core::marker::PhantomData<C>,
+}
+
+impl<C: Clock> PollGapMonitor<C> {
+    /// Create new [`PollGapMonitor`] instance with no maximum gap set
+    pub fn new() -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            max_gap: None,
+            #[cfg(debug_assertions)]
+            last_poll_at: None,
+            #[cfg(debug_assertions)]
+            has_exceeded: false,
+            #[cfg(not(debug_assertions))]
+            _clock: core::marker::PhantomData,
+        }
+    }
+
+    /// Sets the maximum gap between two successive polls
+    ///
+    /// Polling less often than this is latched as a diagnostic, see
+    /// [has_exceeded](#method.has_exceeded). Passing [`None`] (default) disables
+    /// the monitoring. Ignored in the release builds.
+    pub fn set_max_gap(&mut self, max_gap: Option<Milliseconds<C::T>>) {
+        #[cfg(debug_assertions)]
+        {
+            self.max_gap = max_gap;
+            self.last_poll_at = None;
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = max_gap;
+    }
+
+    /// Records the poll at `now`
+    ///
+    /// Returns `true` if the gap since the previous poll exceeded the maximum
+    #[inline]
+    pub fn record(&mut self, now: Instant<C>) -> bool {
+        #[cfg(debug_assertions)]
+        {
+            let Some(max_gap) = self.max_gap else {
+                return false;
+            };
+            let is_exceeded = self
+                .last_poll_at
+                .replace(now)
+                .is_some_and(|at| time::elapsed_since(now, at) > max_gap);
+            self.has_exceeded |= is_exceeded;
+            is_exceeded
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            let _ = now;
+            false
+        }
+    }
+
+    /// Indicates that a gap between the polls exceeded the maximum
+    ///
+    /// The flag is latched until cleared by [take_exceeded](#method.take_exceeded).
+    /// Always `false` in the release builds.
+    pub fn has_exceeded(&self) -> bool {
+        #[cfg(debug_assertions)]
+        return self.has_exceeded;
+        #[cfg(not(debug_assertions))]
+        false
+    }
+
+    /// Returns the latched exceeded gap flag and clears it
+    pub fn take_exceeded(&mut self) -> bool {
+        #[cfg(debug_assertions)]
+        return core::mem::take(&mut self.has_exceeded);
+        #[cfg(not(debug_assertions))]
+        false
+    }
+}
+
+impl<C: Clock> Default for PollGapMonitor<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Poll, PollError, PollGroup, SelfPolled};
+    use crate::mock::MockClock;
+    use crate::time::Instant;
+
+    /// Element counting its polls
+    #[derive(Default)]
+    struct Counter(u32);
+
+    impl Poll<MockClock> for Counter {
+        fn poll_at(&mut self, _now: Instant<MockClock>) -> Result<(), PollError> {
+            self.0 += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn poll_clock_reports_clock_errors() {
+        let clock = MockClock::new();
+        let mut counter = Counter::default();
+        {
+            let mut group = PollGroup::new([&mut counter]);
+            assert_eq!(group.poll_clock(&clock), Ok(()));
+            clock.set_failing(true);
+            assert_eq!(group.poll_clock(&clock), Err(PollError));
+        }
+        assert_eq!(counter.0, 1);
+    }
+
+    #[test]
+    fn self_polled_reports_clock_errors() {
+        let clock = MockClock::new();
+        let mut element = SelfPolled::new(Counter::default(), &clock);
+
+        assert_eq!(element.poll_self(), Ok(()));
+        clock.set_failing(true);
+        assert_eq!(element.poll_self(), Err(PollError));
+        assert_eq!(element.inner().0, 1);
+    }
+}
@@ -0,0 +1,26 @@
+//! Commonly used traits and types
+//!
+//! Glob import this module to get started quickly:
+//! `use embedded_simple_ui::prelude::*;`. The granular paths stay available.
+
+pub use crate::analog::AnalogChannel;
+pub use crate::button::{IndicatorMode, LitButton};
+pub use crate::buzzer::Buzzer;
+pub use crate::clock::{FrameClock, ManualClock};
+pub use crate::encoder::Encoder;
+pub use crate::keypad::{KeyEvent, Keypad};
+pub use crate::knob::Knob;
+pub use crate::led::effects::{self, Effect, EffectKind, EffectType, LedEffect};
+pub use crate::led::{
+    BarGraph, Dimmable, Led, LedGroup, OffState, PinLed, PwmLed, SoftPwmLed, TrafficLight,
+    TrafficLightState,
+};
+pub use crate::poll::{InstantSource, Poll, PollGapMonitor, PollGroup, SelfPolled};
+pub use crate::switch::switch_state::{
+    Polarity, PressedOnHigh, PressedOnLow, PressedOnPolarity, PressedState,
+};
+pub use crate::switch::{
+    AnalogSwitch, Gesture, GestureRecognizer, Inverting, LatchSwitch, PinSwitch, PinSwitchBuilder,
+    ReleaseKind, Switch, SwitchConfigError, SwitchEvent,
+};
+pub use crate::time::{Clock, Hertz, Instant, Milliseconds, UiDuration, UiInstant, UiRate};
@@ -1,7 +1,25 @@
+use bitflags::bitflags;
 use core::marker::PhantomData;
 use embedded_hal::digital::InputPin;
-use embedded_time::duration::Milliseconds;
-use embedded_time::{Clock, Instant};
+use heapless::Deque;
+use num_traits::CheckedMul;
+
+use crate::poll::{Poll, PollError, PollGapMonitor};
+use crate::time::{self, elapsed_since, Clock, FixedPoint, Instant, Milliseconds, TimeInt};
+
+mod analog;
+mod builder;
+mod combo;
+mod gesture;
+mod inverting;
+mod latch;
+
+pub use self::analog::AnalogSwitch;
+pub use self::builder::{PinSwitchBuilder, SwitchConfigError};
+pub use self::combo::Combo;
+pub use self::gesture::{Gesture, GestureRecognizer};
+pub use self::inverting::Inverting;
+pub use self::latch::LatchSwitch;
 
 /// UI Switch
 ///
@@ -11,8 +29,13 @@ use embedded_time::{Clock, Instant};
 /// effect.
 ///
 /// Implementors of this trait should own their resources.
-/// TODO: implement ability to set the default state for the user
 pub trait Switch<C: Clock> {
+    /// Error reported when the switch hardware state can't be read
+    ///
+    /// Switches on infallible GPIOs should use [`core::convert::Infallible`],
+    /// in which case unwrapping the poll results can never panic.
+    type Error;
+
     /// Reset the switch state to initial values
     fn reset(&mut self);
 
@@ -21,11 +44,47 @@ pub trait Switch<C: Clock> {
     /// This must be done in regular intervals in order to make this abstraction
     /// work properly. There might be limits on what this abstraction can track based
     /// on how small / large the intervals are.
-    fn poll(&mut self, now: Instant<C>);
+    ///
+    /// Returns the [`SwitchEvent`] that happened during this poll, if any
+    ///
+    /// Returns an error if the hardware state couldn't be read. The switch state is left
+    /// untouched in such case, so the poll can be simply retried.
+    fn poll(&mut self, now: Instant<C>) -> Result<Option<SwitchEvent<C>>, Self::Error>;
 
-    /// Indicates that the switch state has has_changed since the last poll
+    /// Indicates that the switch state has changed
+    ///
+    /// The change is latched by [poll](#tymethod.poll) and stays set until cleared by
+    /// [take_changed](#method.take_changed) or [clear_changed](#tymethod.clear_changed),
+    /// so the edges are not lost when the switch is polled faster than processed.
     fn has_changed(&self) -> bool;
 
+    /// Clears the latched state change
+    fn clear_changed(&mut self);
+
+    /// Returns the latched state change and clears it
+    fn take_changed(&mut self) -> bool {
+        let has_changed = self.has_changed();
+        self.clear_changed();
+        has_changed
+    }
+
+    /// Polls the switch, reporting the state change in the `nb` fashion
+    ///
+    /// Returns [`nb::Error::WouldBlock`] until a poll reports the [`SwitchEvent::Pressed`]
+    /// or [`SwitchEvent::Released`] event, which is then returned and its latched state
+    /// change cleared. Use [`nb::block!`] to wait for the state change.
+    ///
+    /// The other events reported by the poll are dropped.
+    fn poll_changed(&mut self, now: Instant<C>) -> nb::Result<SwitchEvent<C>, Self::Error> {
+        match self.poll(now).map_err(nb::Error::Other)? {
+            Some(event @ (SwitchEvent::Pressed | SwitchEvent::Released { .. })) => {
+                self.clear_changed();
+                Ok(event)
+            }
+            _ => Err(nb::Error::WouldBlock),
+        }
+    }
+
     /// Indicates that the switch is in pressed state
     fn is_pressed(&self) -> bool;
 
@@ -56,14 +115,141 @@ pub trait Switch<C: Clock> {
     /// Returns the duration for which the current state is held
     ///
     /// Requires an instant to be passed in to compare against the switch state
+    ///
+    /// Saturates to zero if `now` precedes the last state change, e.g. when a stale
+    /// instant is passed in or the clock has rolled over.
     fn current_state(&self, now: Instant<C>) -> Milliseconds<C::T>;
 
     /// Wait for the state to change
     ///
-    /// Polls the switch until it's state has been changed
+    /// Polls the switch until it's state has been changed. Consumes the latched
+    /// state change, returns immediately if there already is one. The polls
+    /// the clock couldn't be read for are skipped.
+    ///
+    /// This operation is blocking
+    fn wait(&mut self, clock: &C) -> Result<(), Self::Error>;
+
+    /// Wait for the state to change or for the `timeout` to elapse
+    ///
+    /// Polls the switch until it's state has been changed or until the `timeout`
+    /// measured from the call has elapsed, whichever comes first.
+    ///
+    /// Returns `true` if the state has changed, `false` if the timeout has elapsed
+    /// or the clock couldn't be read. The latched state change is consumed the same
+    /// way as by [wait](#tymethod.wait).
     ///
     /// This operation is blocking
-    fn wait(&mut self, clock: &C);
+    fn wait_timeout(
+        &mut self,
+        clock: &C,
+        timeout: Milliseconds<C::T>,
+    ) -> Result<bool, Self::Error> {
+        let Ok(started_at) = clock.try_now() else {
+            return Ok(false);
+        };
+        loop {
+            let Ok(now) = clock.try_now() else {
+                return Ok(false);
+            };
+            self.poll(now)?;
+            if self.take_changed() {
+                return Ok(true);
+            }
+            if elapsed_since(now, started_at) >= timeout {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// Event reported by the [`Switch::poll`]
+///
+/// At most one event is returned per poll, state changes take precedence
+/// over the other events. The [`PinSwitch`] buffers all of the events of the poll,
+/// see [`PinSwitch::drain_events`].
+#[derive(Debug)]
+pub enum SwitchEvent<C: Clock> {
+    /// Switch has been pressed
+    Pressed,
+    /// Switch has been released after being held for `held_for`, `kind` tells
+    /// a quick tap and a long press apart
+    Released {
+        held_for: Milliseconds<C::T>,
+        kind: ReleaseKind,
+    },
+    /// Switch is held and the auto-repeat has fired
+    Repeat,
+    /// Click sequence has finished with `count` clicks (`2` for a double click)
+    Clicks(u8),
+    /// Switch has been held past the stuck threshold, e.g. due to a stuck button
+    /// or a shorted wire. Reported once per press.
+    Fault,
+    /// Switch has been held for the confirm threshold, see
+    /// [`PinSwitch::set_confirm_threshold`]. Reported once per press, after
+    /// the other events of the same poll.
+    Confirmed,
+}
+
+/// Kind of the press ended by the [`SwitchEvent::Released`]
+///
+/// Switches without a long press threshold report every release as a [`ReleaseKind::Tap`],
+/// see [`PinSwitch::set_long_press_threshold`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReleaseKind {
+    /// Switch has been held for less than the long press threshold
+    Tap,
+    /// Switch has been held for at least the long press threshold
+    LongPress,
+}
+
+// manual impls, the derives would require the clock itself to be `Copy` or `PartialEq`
+impl<C: Clock> Clone for SwitchEvent<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Clock> Copy for SwitchEvent<C> {}
+
+impl<C: Clock> PartialEq for SwitchEvent<C> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                SwitchEvent::Released { held_for, kind },
+                SwitchEvent::Released {
+                    held_for: other_held_for,
+                    kind: other_kind,
+                },
+            ) => held_for == other_held_for && kind == other_kind,
+            (SwitchEvent::Clicks(count), SwitchEvent::Clicks(other_count)) => count == other_count,
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+impl<C: Clock> Eq for SwitchEvent<C> {}
+
+#[cfg(feature = "defmt")]
+impl<C: Clock> defmt::Format for SwitchEvent<C>
+where
+    C::T: defmt::Format,
+{
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            SwitchEvent::Pressed => defmt::write!(f, "Pressed"),
+            SwitchEvent::Released { held_for, kind } => defmt::write!(
+                f,
+                "Released {{ held_for: {}ms, kind: {} }}",
+                held_for.integer(),
+                kind
+            ),
+            SwitchEvent::Repeat => defmt::write!(f, "Repeat"),
+            SwitchEvent::Clicks(count) => defmt::write!(f, "Clicks({})", count),
+            SwitchEvent::Fault => defmt::write!(f, "Fault"),
+            SwitchEvent::Confirmed => defmt::write!(f, "Confirmed"),
+        }
+    }
 }
 
 pub mod switch_state {
@@ -74,7 +260,7 @@ pub mod switch_state {
     /// This is internally implemented for [`PressedOnHigh`] and [`PressedOnLow`]
     /// to implement different behaviors.
     pub trait PressedState {
-        fn get_pressed_state<P: InputPin>(pin: &mut P) -> bool;
+        fn get_pressed_state<P: InputPin>(pin: &mut P) -> Result<bool, P::Error>;
     }
 
     /// Sets the switch behavior to be in pressed state when the pin is high
@@ -83,90 +269,982 @@ pub mod switch_state {
     /// Sets the switch behavior to be in pressed state when the pin is is low
     pub struct PressedOnLow {}
 
+    /// Sets the switch behavior at runtime, see [`PinSwitch::set_polarity`](super::PinSwitch::set_polarity)
+    ///
+    /// Behaves as [`PressedOnHigh`] until the polarity is set.
+    pub struct PressedOnPolarity {}
+
+    /// Pin level at which the switch is in pressed state
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Polarity {
+        /// Pressed when the pin is high
+        ActiveHigh,
+        /// Pressed when the pin is low
+        ActiveLow,
+    }
+
+    impl Polarity {
+        /// Reads the pressed state of the `pin` with this polarity
+        pub fn get_pressed_state<P: InputPin>(self, pin: &mut P) -> Result<bool, P::Error> {
+            match self {
+                Polarity::ActiveHigh => pin.is_high(),
+                Polarity::ActiveLow => pin.is_low(),
+            }
+        }
+    }
+
     impl PressedState for PressedOnHigh {
-        fn get_pressed_state<P: InputPin>(pin: &mut P) -> bool {
-            pin.is_high().unwrap()
+        fn get_pressed_state<P: InputPin>(pin: &mut P) -> Result<bool, P::Error> {
+            pin.is_high()
         }
     }
     impl PressedState for PressedOnLow {
-        fn get_pressed_state<P: InputPin>(pin: &mut P) -> bool {
-            pin.is_low().unwrap()
+        fn get_pressed_state<P: InputPin>(pin: &mut P) -> Result<bool, P::Error> {
+            pin.is_low()
         }
     }
+    impl PressedState for PressedOnPolarity {
+        fn get_pressed_state<P: InputPin>(pin: &mut P) -> Result<bool, P::Error> {
+            pin.is_high()
+        }
+    }
+}
+
+bitflags! {
+    /// State flags of the [`PinSwitch`]
+    ///
+    /// Allows testing for multiple switch conditions with a single bitwise check
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SwitchFlags: u8 {
+        /// Switch is in pressed state
+        const PRESSED = 1 << 0;
+        /// Switch state has changed, latched until cleared, see [`Switch::has_changed`]
+        const CHANGED = 1 << 1;
+        /// Auto-repeat has fired during the last poll
+        const REPEAT = 1 << 2;
+        /// Switch has been pressed during the last poll
+        const JUST_PRESSED = 1 << 3;
+        /// Switch has been released during the last poll
+        const JUST_RELEASED = 1 << 4;
+        /// Switch has been held past the stuck threshold, see [`PinSwitch::set_stuck_threshold`]
+        const STUCK = 1 << 5;
+        /// Switch has been held for the confirm threshold, see [`PinSwitch::set_confirm_threshold`]
+        const CONFIRMED = 1 << 6;
+        /// Pin has been read during the last poll, see [`PinSwitch::did_io`]
+        const PIN_READ = 1 << 7;
+    }
 }
 
-// TODO: instead of bools check if we can use bitflags crate to get more efficient and ergonomic
+#[cfg(feature = "defmt")]
+impl defmt::Format for SwitchFlags {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "SwitchFlags({=u8:#b})", self.bits())
+    }
+}
+
+/// Pin level read ahead of the poll, see [`PinSwitch::poll_raw`]
+struct RawLevel(bool);
+
+impl embedded_hal::digital::ErrorType for RawLevel {
+    type Error = core::convert::Infallible;
+}
+
+impl InputPin for RawLevel {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.0)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.0)
+    }
+}
+
+/// Snapshot of the [`PinSwitch`] state
+///
+/// Captures the committed switch state with its timing as the offsets from the moment
+/// the snapshot was taken, so it can be restored on a clock with a different epoch,
+/// e.g. after a reset. See [`PinSwitch::snapshot`] and [`PinSwitch::restore`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SwitchSnapshot<T: TimeInt = u32> {
+    /// Switch is in pressed state
+    pub is_pressed: bool,
+    /// Number of debounced presses, see [`PinSwitch::press_count`]
+    pub press_count: u32,
+    /// Number of debounced releases, see [`PinSwitch::release_count`]
+    pub release_count: u32,
+    /// Milliseconds for which the current state has been held
+    pub state_held_ms: T,
+    /// Milliseconds for which the previous state lasted
+    pub prev_state_lasted_ms: T,
+}
+
+/// Default maximum number of events buffered by a single [`PinSwitch`]
+///
+/// See the `N` parameter of the [`PinSwitch`]
+pub const SWITCH_EVENT_CAPACITY: usize = 8;
 
 /// Switch implementation for [`InputPin`] of `embedded_hal`
-pub struct PinSwitch<P: InputPin, S: switch_state::PressedState, C: Clock> {
+///
+/// The switch can optionally debounce the raw pin readings, see
+/// [with_debounce](#method.with_debounce) and [with_sample_debounce](#method.with_sample_debounce).
+///
+/// Up to `N` events are buffered, see [drain_events](#method.drain_events). Each slot
+/// takes the size of the event plus its instant, i.e. 12 bytes with a `u32` clock.
+pub struct PinSwitch<
+    P: InputPin,
+    S: switch_state::PressedState,
+    C: Clock,
+    const N: usize = SWITCH_EVENT_CAPACITY,
+> {
     pin: P,
-    is_pressed: bool,
-    has_changed: bool,
+    flags: SwitchFlags,
     last_change_at: Instant<C>,
     prev_state_lasted: Milliseconds<C::T>,
+    debounce: Milliseconds<C::T>,
+    release_debounce: Milliseconds<C::T>,
+    pending_since: Option<Instant<C>>,
+    sample_debounce: u8,
+    pending_samples: u8,
+    click_timeout: Option<Milliseconds<C::T>>,
+    click_count: u8,
+    last_release_at: Option<Instant<C>>,
+    finished_clicks: Option<u8>,
+    repeat_delay: Milliseconds<C::T>,
+    repeat_interval: Option<Milliseconds<C::T>>,
+    next_repeat_after: Milliseconds<C::T>,
+    press_count: u32,
+    release_count: u32,
+    keep_counters: bool,
+    default_pressed: bool,
+    stuck_threshold: Option<Milliseconds<C::T>>,
+    long_press_threshold: Option<Milliseconds<C::T>>,
+    confirm_threshold: Option<Milliseconds<C::T>>,
+    events: Deque<(SwitchEvent<C>, Instant<C>), N>,
+    synthetic_now: Instant<C>,
+    polarity: Option<switch_state::Polarity>,
+    poll_gap: PollGapMonitor<C>,
     pressed_state: PhantomData<S>,
 }
 
-impl<P: InputPin, S: switch_state::PressedState, C: Clock> PinSwitch<P, S, C> {
+impl<P: InputPin, S: switch_state::PressedState, C: Clock, const N: usize> PinSwitch<P, S, C, N> {
     /// Create new [`PinSwitch`] instance for the passed in `pin`
     pub fn new(pin: P) -> Self {
         Self {
             pin,
-            is_pressed: false,
-            has_changed: false,
+            flags: SwitchFlags::empty(),
             last_change_at: Instant::<C>::new(C::T::from(0)),
             prev_state_lasted: Milliseconds::<C::T>::new(C::T::from(0)),
+            debounce: Milliseconds::<C::T>::new(C::T::from(0)),
+            release_debounce: Milliseconds::<C::T>::new(C::T::from(0)),
+            pending_since: None,
+            sample_debounce: 0,
+            pending_samples: 0,
+            click_timeout: None,
+            click_count: 0,
+            last_release_at: None,
+            finished_clicks: None,
+            repeat_delay: Milliseconds::<C::T>::new(C::T::from(0)),
+            repeat_interval: None,
+            next_repeat_after: Milliseconds::<C::T>::new(C::T::from(0)),
+            press_count: 0,
+            release_count: 0,
+            keep_counters: false,
+            default_pressed: false,
+            stuck_threshold: None,
+            long_press_threshold: None,
+            confirm_threshold: None,
+            events: Deque::new(),
+            synthetic_now: Instant::<C>::new(C::T::from(0)),
+            polarity: None,
+            poll_gap: PollGapMonitor::new(),
             pressed_state: Default::default(),
         }
     }
-}
 
-impl<P: InputPin, S: switch_state::PressedState, C: Clock> Switch<C> for PinSwitch<P, S, C> {
-    fn poll(&mut self, now: Instant<C>) {
-        let new_state = S::get_pressed_state(&mut self.pin);
+    /// Sets the maximum gap between two successive polls, checked in the debug builds
+    ///
+    /// See [set_max_poll_gap](#method.set_max_poll_gap)
+    pub fn with_max_poll_gap(mut self, max_gap: Milliseconds<C::T>) -> Self {
+        self.set_max_poll_gap(Some(max_gap));
+        self
+    }
+
+    /// Sets the maximum gap between two successive polls, see [`PollGapMonitor::set_max_gap`]
+    pub fn set_max_poll_gap(&mut self, max_gap: Option<Milliseconds<C::T>>) {
+        self.poll_gap.set_max_gap(max_gap);
+    }
+
+    /// Indicates that the switch has been polled less often than the maximum poll gap
+    ///
+    /// Latched until cleared by [take_poll_gap_exceeded](#method.take_poll_gap_exceeded).
+    /// Always `false` in the release builds.
+    pub fn poll_gap_exceeded(&self) -> bool {
+        self.poll_gap.has_exceeded()
+    }
+
+    /// Returns the latched exceeded poll gap and clears it
+    pub fn take_poll_gap_exceeded(&mut self) -> bool {
+        self.poll_gap.take_exceeded()
+    }
+
+    /// Sets the pressed polarity of the switch at runtime
+    ///
+    /// See [set_polarity](#method.set_polarity)
+    pub fn with_polarity(mut self, polarity: switch_state::Polarity) -> Self {
+        self.set_polarity(Some(polarity));
+        self
+    }
+
+    /// Sets the pressed polarity of the switch at runtime
+    ///
+    /// Overrides the polarity given by the `S` type parameter, e.g. to support
+    /// multiple board revisions with different wiring from one binary. Use
+    /// [`PressedOnPolarity`](switch_state::PressedOnPolarity) as `S` for the switches
+    /// configured only at runtime.
+    ///
+    /// Passing [`None`] falls back to the polarity of the `S` type parameter.
+    pub fn set_polarity(&mut self, polarity: Option<switch_state::Polarity>) {
+        self.polarity = polarity;
+    }
+
+    /// Returns the pressed polarity set at runtime
+    ///
+    /// Returns [`None`] if the polarity is given by the `S` type parameter
+    pub fn polarity(&self) -> Option<switch_state::Polarity> {
+        self.polarity
+    }
+
+    /// Sets the debounce window of the switch
+    ///
+    /// See [set_debounce](#method.set_debounce)
+    pub fn with_debounce(mut self, debounce: Milliseconds<C::T>) -> Self {
+        self.set_debounce(debounce);
+        self
+    }
+
+    /// Sets the debounce window of the switch
+    ///
+    /// A state change is committed only after the raw pin reading has been
+    /// stable for the whole window. The state change is then recorded
+    /// as if it happened when the new reading first appeared.
+    ///
+    /// Zero window (default) disables the debouncing.
+    ///
+    /// Applies to both the presses and the releases, see
+    /// [set_debounce_windows](#method.set_debounce_windows).
+    pub fn set_debounce(&mut self, debounce: Milliseconds<C::T>) {
+        self.set_debounce_windows(debounce, debounce);
+    }
+
+    /// Returns the debounce window of the presses
+    ///
+    /// See [debounce_windows](#method.debounce_windows)
+    pub fn debounce(&self) -> Milliseconds<C::T> {
+        self.debounce
+    }
+
+    /// Sets separate debounce windows for the presses and the releases
+    ///
+    /// See [set_debounce_windows](#method.set_debounce_windows)
+    pub fn with_debounce_windows(
+        mut self,
+        press: Milliseconds<C::T>,
+        release: Milliseconds<C::T>,
+    ) -> Self {
+        self.set_debounce_windows(press, release);
+        self
+    }
+
+    /// Sets separate debounce windows for the presses and the releases
+    ///
+    /// Works the same way as [set_debounce](#method.set_debounce), the window
+    /// is picked by the direction of the pending state change. Suits the contacts
+    /// bouncing more on one of the edges.
+    pub fn set_debounce_windows(&mut self, press: Milliseconds<C::T>, release: Milliseconds<C::T>) {
+        self.debounce = press;
+        self.release_debounce = release;
+    }
+
+    /// Returns the `(press, release)` debounce windows of the switch
+    pub fn debounce_windows(&self) -> (Milliseconds<C::T>, Milliseconds<C::T>) {
+        (self.debounce, self.release_debounce)
+    }
+
+    /// Sets the number of consecutive samples required to accept a state change
+    ///
+    /// See [set_sample_debounce](#method.set_sample_debounce)
+    pub fn with_sample_debounce(mut self, samples: u8) -> Self {
+        self.set_sample_debounce(samples);
+        self
+    }
+
+    /// Sets the number of consecutive samples required to accept a state change
+    ///
+    /// A state change is committed only after the raw pin reading has differed from
+    /// the current state in `samples` polls in a row, regardless of the time between
+    /// them. Suits the loops polling at a fixed rate without a real clock, see
+    /// [poll_delta](#method.poll_delta). Combines with the time based
+    /// [debounce](#method.set_debounce), both have to be satisfied.
+    ///
+    /// Zero or one sample (default) disables the sample counting.
+    pub fn set_sample_debounce(&mut self, samples: u8) {
+        self.sample_debounce = samples;
+        self.pending_samples = 0;
+    }
+
+    /// Returns the number of consecutive samples required to accept a state change
+    pub fn sample_debounce(&self) -> u8 {
+        self.sample_debounce
+    }
+
+    /// Enables click counting with the given inter-click timeout
+    ///
+    /// See [set_click_timeout](#method.set_click_timeout)
+    pub fn with_click_timeout(mut self, timeout: Milliseconds<C::T>) -> Self {
+        self.set_click_timeout(Some(timeout));
+        self
+    }
+
+    /// Sets the inter-click timeout used for multi-click detection
+    ///
+    /// Each release that happens within the timeout of the previous release is counted
+    /// as another click of the same sequence. Once no further click comes within
+    /// the timeout, the sequence is finished and its click count can be retrieved
+    /// by [take_click_count](#method.take_click_count).
+    ///
+    /// Passing [`None`] disables the click counting.
+    pub fn set_click_timeout(&mut self, timeout: Option<Milliseconds<C::T>>) {
+        self.click_timeout = timeout;
+        self.click_count = 0;
+        self.last_release_at = None;
+        self.finished_clicks = None;
+    }
+
+    /// Returns the click count of the last finished click sequence
+    ///
+    /// Returns `Some(1)` for a single click, `Some(2)` for a double click, etc.
+    /// The count is returned only once, subsequent calls return [`None`]
+    /// until another click sequence finishes.
+    ///
+    /// Returns [`None`] if no sequence has finished by `now` or if click counting
+    /// is disabled.
+    pub fn take_click_count(&mut self, now: Instant<C>) -> Option<u8> {
+        if let Some(clicks) = self.finished_clicks.take() {
+            return Some(clicks);
+        }
+
+        self.finish_clicks(now);
+        self.finished_clicks.take()
+    }
+
+    /// Enables auto-repeat while the switch is held
+    ///
+    /// See [set_repeat](#method.set_repeat)
+    pub fn with_repeat(
+        mut self,
+        initial_delay: Milliseconds<C::T>,
+        interval: Milliseconds<C::T>,
+    ) -> Self {
+        self.set_repeat(initial_delay, interval);
+        self
+    }
+
+    /// Enables auto-repeat while the switch is held
+    ///
+    /// Like a held key on a keyboard, the switch fires the first repeat after
+    /// being held for `initial_delay` and then every `interval` until released.
+    /// Repeats missed due to a long gap between polls are skipped rather than
+    /// fired in a burst.
+    ///
+    /// Zero `interval` is treated as one millisecond.
+    pub fn set_repeat(&mut self, initial_delay: Milliseconds<C::T>, interval: Milliseconds<C::T>) {
+        let interval = if interval.integer() == C::T::from(0) {
+            Milliseconds::<C::T>::new(C::T::from(1))
+        } else {
+            interval
+        };
+        self.repeat_delay = initial_delay;
+        self.repeat_interval = Some(interval);
+        self.next_repeat_after = initial_delay;
+        self.flags.remove(SwitchFlags::REPEAT);
+    }
+
+    /// Disables auto-repeat
+    pub fn clear_repeat(&mut self) {
+        self.repeat_interval = None;
+        self.flags.remove(SwitchFlags::REPEAT);
+    }
+
+    /// Returns the current state flags of the switch
+    pub fn flags(&self) -> SwitchFlags {
+        self.flags
+    }
+
+    /// Indicates that an auto-repeat has fired during the last poll
+    pub fn repeat_fired(&self) -> bool {
+        self.flags.contains(SwitchFlags::REPEAT)
+    }
+
+    /// Returns the number of debounced presses since the switch was created
+    ///
+    /// Saturates at [`u32::MAX`]. See [reset_counters](#method.reset_counters)
+    pub fn press_count(&self) -> u32 {
+        self.press_count
+    }
+
+    /// Returns the number of debounced releases since the switch was created
+    ///
+    /// Saturates at [`u32::MAX`]. See [reset_counters](#method.reset_counters)
+    pub fn release_count(&self) -> u32 {
+        self.release_count
+    }
+
+    /// Clears the press and release counters
+    pub fn reset_counters(&mut self) {
+        self.press_count = 0;
+        self.release_count = 0;
+    }
+
+    /// Returns a mutable reference to the switch pin
+    ///
+    /// The debounced state is kept, if the pin reads differently after being
+    /// reconfigured, the next poll reports the state change as usual.
+    pub fn pin_mut(&mut self) -> &mut P {
+        &mut self.pin
+    }
+
+    /// Releases the switch pin
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
 
-        if new_state == self.is_pressed {
-            self.has_changed = false;
+    /// Takes a snapshot of the switch state at `now`
+    ///
+    /// Pending debounce, click sequence and the buffered events are not captured
+    pub fn snapshot(&self, now: Instant<C>) -> SwitchSnapshot<C::T> {
+        SwitchSnapshot {
+            is_pressed: self.is_pressed(),
+            press_count: self.press_count,
+            release_count: self.release_count,
+            state_held_ms: self.current_state(now).integer(),
+            prev_state_lasted_ms: self.prev_state_lasted.integer(),
+        }
+    }
+
+    /// Restores the switch state from the `snapshot`, rebasing it onto `now`
+    ///
+    /// The current state is taken as held for the captured duration up to `now`.
+    /// If the pin reads differently, the next poll reports the state change as usual.
+    pub fn restore(&mut self, snapshot: SwitchSnapshot<C::T>, now: Instant<C>) {
+        self.flags = SwitchFlags::empty();
+        self.flags.set(SwitchFlags::PRESSED, snapshot.is_pressed);
+        self.press_count = snapshot.press_count;
+        self.release_count = snapshot.release_count;
+        self.last_change_at = time::rewind(now, Milliseconds::<C::T>::new(snapshot.state_held_ms));
+        self.prev_state_lasted = Milliseconds::<C::T>::new(snapshot.prev_state_lasted_ms);
+        self.pending_since = None;
+        self.pending_samples = 0;
+        self.click_count = 0;
+        self.last_release_at = None;
+        self.finished_clicks = None;
+        self.events.clear();
+    }
+
+    /// Sets the state the switch starts in and returns to on the [`Switch::reset`]
+    ///
+    /// See [set_default_pressed](#method.set_default_pressed)
+    pub fn with_default_pressed(mut self, is_pressed: bool) -> Self {
+        self.set_default_pressed(is_pressed);
+        self
+    }
+
+    /// Sets the state the switch starts in and returns to on the [`Switch::reset`]
+    ///
+    /// The switch is released by default. Normally closed contacts resting in the
+    /// pressed state should start pressed, so that the first poll doesn't report
+    /// a spurious state change.
+    ///
+    /// The switch is put into the default state right away, without reporting
+    /// a state change.
+    pub fn set_default_pressed(&mut self, is_pressed: bool) {
+        self.default_pressed = is_pressed;
+        self.flags.set(SwitchFlags::PRESSED, is_pressed);
+    }
+
+    /// Returns the state the switch starts in and returns to on the [`Switch::reset`]
+    pub fn default_pressed(&self) -> bool {
+        self.default_pressed
+    }
+
+    /// Sets whether the press and release counters survive the [`Switch::reset`]
+    ///
+    /// See [set_keep_counters](#method.set_keep_counters)
+    pub fn with_keep_counters(mut self, keep_counters: bool) -> Self {
+        self.set_keep_counters(keep_counters);
+        self
+    }
+
+    /// Sets whether the press and release counters survive the [`Switch::reset`]
+    ///
+    /// The counters are cleared by the reset by default.
+    pub fn set_keep_counters(&mut self, keep_counters: bool) {
+        self.keep_counters = keep_counters;
+    }
+
+    /// Enables the stuck switch detection with the given threshold
+    ///
+    /// See [set_stuck_threshold](#method.set_stuck_threshold)
+    pub fn with_stuck_threshold(mut self, threshold: Milliseconds<C::T>) -> Self {
+        self.set_stuck_threshold(Some(threshold));
+        self
+    }
+
+    /// Sets the threshold for the stuck switch detection
+    ///
+    /// A switch continuously pressed for longer than the `threshold` is considered
+    /// stuck, [poll](Switch::poll) reports the [`SwitchEvent::Fault`] once it happens.
+    ///
+    /// Passing [`None`] (default) disables the detection.
+    pub fn set_stuck_threshold(&mut self, threshold: Option<Milliseconds<C::T>>) {
+        self.stuck_threshold = threshold;
+    }
+
+    /// Sets the threshold telling the taps and the long presses apart
+    ///
+    /// See [set_long_press_threshold](#method.set_long_press_threshold)
+    pub fn with_long_press_threshold(mut self, threshold: Milliseconds<C::T>) -> Self {
+        self.set_long_press_threshold(Some(threshold));
+        self
+    }
+
+    /// Sets the threshold telling the taps and the long presses apart
+    ///
+    /// Releases after the switch has been held for at least the `threshold` are
+    /// reported as the [`ReleaseKind::LongPress`], the shorter ones as the [`ReleaseKind::Tap`].
+    ///
+    /// Passing [`None`] (default) reports all releases as the taps.
+    pub fn set_long_press_threshold(&mut self, threshold: Option<Milliseconds<C::T>>) {
+        self.long_press_threshold = threshold;
+    }
+
+    /// Sets the hold duration confirming an action
+    ///
+    /// See [set_confirm_threshold](#method.set_confirm_threshold)
+    pub fn with_confirm_threshold(mut self, threshold: Milliseconds<C::T>) -> Self {
+        self.set_confirm_threshold(Some(threshold));
+        self
+    }
+
+    /// Sets the hold duration confirming an action, e.g. for the destructive actions
+    /// requiring the switch to be held
+    ///
+    /// [poll](Switch::poll) reports the [`SwitchEvent::Confirmed`] once the switch has
+    /// been continuously pressed for the `threshold`, the progress towards it is
+    /// reported by [confirm_progress](#method.confirm_progress). If another event
+    /// happens within the same poll, e.g. an auto-repeat, the poll returns that one
+    /// and the confirmation follows it in the [buffer](#method.drain_events).
+    ///
+    /// Passing [`None`] (default) disables the confirmation.
+    pub fn set_confirm_threshold(&mut self, threshold: Option<Milliseconds<C::T>>) {
+        self.confirm_threshold = threshold;
+    }
+
+    /// Returns the progress of the hold towards the confirm threshold at `now`
+    ///
+    /// The progress is a Q16 fixed point fraction, i.e. `0` while released and
+    /// `0x1_0000` once the hold is confirmed, e.g. for filling a progress bar.
+    /// Releasing the switch early resets the progress.
+    ///
+    /// Always `0` if the confirmation is disabled
+    pub fn confirm_progress(&self, now: Instant<C>) -> u32 {
+        const ONE: u32 = 1 << 16;
+        let Some(threshold) = self.confirm_threshold.map(|dur| dur.integer()) else {
+            return 0;
+        };
+        if !self.is_pressed() {
+            return 0;
+        }
+        let held = self.current_state(now).integer();
+        if held >= threshold {
+            return ONE;
+        }
+
+        // the held time is below the threshold, so the fraction is below one
+        let one = C::T::from(ONE);
+        let progress = match held.checked_mul(&one) {
+            Some(scaled) => scaled / threshold,
+            None => held / (threshold / one),
+        };
+        time::saturating_u32(progress).min(ONE - 1)
+    }
+
+    /// Indicates that the switch has been continuously pressed for longer than
+    /// the stuck threshold
+    ///
+    /// Always `false` if the stuck detection is disabled
+    pub fn is_stuck(&self, now: Instant<C>) -> bool {
+        self.stuck_threshold
+            .is_some_and(|threshold| self.is_pressed() && self.current_state(now) > threshold)
+    }
+
+    /// Polls the switch advancing its own synthetic clock by the `elapsed` time
+    ///
+    /// See [`PinLed::poll_delta`](crate::led::PinLed::poll_delta)
+    pub fn poll_delta(
+        &mut self,
+        elapsed: Milliseconds<C::T>,
+    ) -> Result<Option<SwitchEvent<C>>, P::Error> {
+        self.synthetic_now = time::advance(self.synthetic_now, elapsed);
+        self.poll(self.synthetic_now)
+    }
+
+    /// Polls the switch with the pin level read ahead by the caller
+    ///
+    /// Works the same way as [poll](Switch::poll), but takes the raw pin level
+    /// (`true` if high) instead of reading the pin, so the possibly slow read, e.g. from
+    /// a GPIO expander, can take place outside of an interrupt handler or a critical
+    /// section, leaving just the cheap state update inside of it.
+    ///
+    /// The pressed polarity is applied to the raw level as usual.
+    pub fn poll_raw(&mut self, is_high: bool, now: Instant<C>) -> Option<SwitchEvent<C>> {
+        self.flags.remove(SwitchFlags::PIN_READ);
+        self.poll_gap.record(now);
+        self.poll_event(is_high, now)
+    }
+
+    /// Drains the buffered events, oldest first
+    ///
+    /// Every event of the polls is buffered, including the ones happening within
+    /// the same poll as the event returned by [poll](Switch::poll), so the events
+    /// can be processed at a different pace than the switch is polled. Once the buffer
+    /// is full, i.e. holds `N` events, the oldest event is dropped, see [`PinSwitch`].
+    pub fn drain_events(&mut self) -> impl Iterator<Item = SwitchEvent<C>> + '_ {
+        self.drain_timed_events().map(|(event, _)| event)
+    }
+
+    /// Drains the buffered events along with their instants, oldest first
+    ///
+    /// The state changes carry the instant of the committed transition, i.e. when
+    /// the reading started to settle, see [last_change_instant](#method.last_change_instant).
+    /// The other events carry the instant of the poll that reported them.
+    ///
+    /// See [drain_events](#method.drain_events)
+    pub fn drain_timed_events(
+        &mut self,
+    ) -> impl Iterator<Item = (SwitchEvent<C>, Instant<C>)> + '_ {
+        core::iter::from_fn(move || self.events.pop_front())
+    }
+
+    /// Returns the instant of the last committed state change
+    ///
+    /// The instant the reading started to settle, not the one the debounce
+    /// has finished at. Returns the epoch of the clock until the first change.
+    pub fn last_change_instant(&self) -> Instant<C> {
+        self.last_change_at
+    }
+
+    /// Indicates that the switch has been pressed during the last poll
+    ///
+    /// Unlike the latched [`Switch::has_changed`], the edge is reported only by the poll
+    /// in which the press has been committed
+    pub fn just_pressed(&self) -> bool {
+        self.flags.contains(SwitchFlags::JUST_PRESSED)
+    }
+
+    /// Indicates that the switch has been released during the last poll
+    ///
+    /// See [just_pressed](#method.just_pressed)
+    pub fn just_released(&self) -> bool {
+        self.flags.contains(SwitchFlags::JUST_RELEASED)
+    }
+
+    /// Indicates that the pin has been read during the last poll
+    ///
+    /// Always `true` after [poll](Switch::poll) and `false` after [poll_raw](#method.poll_raw),
+    /// e.g. for verifying the bus load caused by the polls of a GPIO expander.
+    pub fn did_io(&self) -> bool {
+        self.flags.contains(SwitchFlags::PIN_READ)
+    }
+
+    /// Debounces the raw reading and commits the state change once it settles
+    ///
+    /// Returns `true` if the committed state has changed
+    fn update_state(&mut self, new_state: bool, now: Instant<C>) -> bool {
+        if new_state == self.is_pressed() {
+            self.pending_since = None;
+            self.pending_samples = 0;
+            return false;
+        }
+
+        // the reading differs from the committed state, wait for it to settle
+        let changed_at = *self.pending_since.get_or_insert(now);
+        self.pending_samples = self.pending_samples.saturating_add(1);
+        let debounce = match new_state {
+            true => self.debounce,
+            false => self.release_debounce,
+        };
+        if elapsed_since(now, changed_at) < debounce || self.pending_samples < self.sample_debounce
+        {
+            return false;
+        }
+
+        self.pending_since = None;
+        self.pending_samples = 0;
+        self.flags.set(SwitchFlags::PRESSED, new_state);
+        if new_state {
+            self.press_count = self.press_count.saturating_add(1);
+        } else {
+            self.release_count = self.release_count.saturating_add(1);
+        }
+        self.prev_state_lasted = self.current_state(changed_at);
+        self.last_change_at = changed_at;
+        self.track_clicks(changed_at);
+        true
+    }
+
+    /// Buffers the event, dropping the oldest one if the buffer is full
+    fn buffer_event(&mut self, event: SwitchEvent<C>, now: Instant<C>) {
+        if self.events.is_full() {
+            self.events.pop_front();
+        }
+        let at = match event {
+            SwitchEvent::Pressed | SwitchEvent::Released { .. } => self.last_change_at,
+            _ => now,
+        };
+        // can't fail, there is a free slot now
+        let _ = self.events.push_back((event, at));
+    }
+
+    /// Updates the switch state from the raw pin level
+    ///
+    /// Buffers all of the events of this poll, returns the first one
+    fn poll_event(&mut self, is_high: bool, now: Instant<C>) -> Option<SwitchEvent<C>> {
+        let mut level = RawLevel(is_high);
+        let new_state = match self.polarity {
+            Some(polarity) => polarity.get_pressed_state(&mut level),
+            None => S::get_pressed_state(&mut level),
+        }
+        .unwrap_or_else(|never| match never {});
+
+        let has_changed = self.update_state(new_state, now);
+        if has_changed {
+            self.flags.insert(SwitchFlags::CHANGED);
+        }
+        let is_pressed = self.is_pressed();
+        self.flags
+            .set(SwitchFlags::JUST_PRESSED, has_changed && is_pressed);
+        self.flags
+            .set(SwitchFlags::JUST_RELEASED, has_changed && !is_pressed);
+        self.track_repeat(now, has_changed);
+        let has_become_stuck = self.track_stuck(now);
+        let has_confirmed = self.track_confirm(now);
+
+        let state_change = has_changed.then(|| match self.is_pressed() {
+            true => SwitchEvent::Pressed,
+            false => {
+                let is_long_press = self
+                    .long_press_threshold
+                    .is_some_and(|threshold| self.prev_state_lasted >= threshold);
+                SwitchEvent::Released {
+                    held_for: self.prev_state_lasted,
+                    kind: match is_long_press {
+                        true => ReleaseKind::LongPress,
+                        false => ReleaseKind::Tap,
+                    },
+                }
+            }
+        });
+        let events = [
+            state_change,
+            has_become_stuck.then_some(SwitchEvent::Fault),
+            self.repeat_fired().then_some(SwitchEvent::Repeat),
+            self.finish_clicks(now).map(SwitchEvent::Clicks),
+            // reported on top of the other events, never in place of them
+            has_confirmed.then_some(SwitchEvent::Confirmed),
+        ];
+
+        let mut first = None;
+        for event in events.into_iter().flatten() {
+            self.buffer_event(event, now);
+            first = first.or(Some(event));
+        }
+        first
+    }
+
+    /// Tracks the stuck switch, returns `true` once the switch has become stuck
+    fn track_stuck(&mut self, now: Instant<C>) -> bool {
+        let is_stuck = self.is_stuck(now);
+        let has_become_stuck = is_stuck && !self.flags.contains(SwitchFlags::STUCK);
+        self.flags.set(SwitchFlags::STUCK, is_stuck);
+        has_become_stuck
+    }
+
+    /// Tracks the confirming hold, returns `true` once the hold has been confirmed
+    fn track_confirm(&mut self, now: Instant<C>) -> bool {
+        let is_confirmed = self.confirm_progress(now) == 1 << 16;
+        let has_confirmed = is_confirmed && !self.flags.contains(SwitchFlags::CONFIRMED);
+        self.flags.set(SwitchFlags::CONFIRMED, is_confirmed);
+        has_confirmed
+    }
+
+    /// Fires the auto-repeat while the switch is held
+    fn track_repeat(&mut self, now: Instant<C>, has_changed: bool) {
+        self.flags.remove(SwitchFlags::REPEAT);
+        let Some(interval) = self.repeat_interval else {
+            return;
+        };
+        if !self.is_pressed() {
+            return;
+        }
+        if has_changed {
+            self.next_repeat_after = self.repeat_delay;
+        }
+
+        let held_for = elapsed_since(now, self.last_change_at);
+        if held_for >= self.next_repeat_after {
+            self.flags.insert(SwitchFlags::REPEAT);
+            let missed =
+                (held_for.integer() - self.next_repeat_after.integer()) / interval.integer();
+            self.next_repeat_after = Milliseconds::<C::T>::new(
+                self.next_repeat_after.integer() + (missed + C::T::from(1)) * interval.integer(),
+            );
+        }
+    }
+
+    /// Finishes the pending click sequence once the inter-click timeout elapses
+    ///
+    /// Returns the click count of the sequence if it has been finished
+    fn finish_clicks(&mut self, now: Instant<C>) -> Option<u8> {
+        let timeout = self.click_timeout?;
+        let last_release_at = self.last_release_at?;
+        if self.is_pressed() || elapsed_since(now, last_release_at) < timeout {
+            return None;
+        }
+
+        self.last_release_at = None;
+        self.finished_clicks = Some(core::mem::take(&mut self.click_count));
+        self.finished_clicks
+    }
+
+    /// Tracks the click sequence on the committed state change
+    fn track_clicks(&mut self, changed_at: Instant<C>) {
+        let Some(timeout) = self.click_timeout else {
             return;
+        };
+
+        if self.is_pressed() {
+            // press after the timeout starts a new sequence, finish the pending one
+            if let Some(last_release_at) = self.last_release_at {
+                if elapsed_since(changed_at, last_release_at) >= timeout {
+                    self.finished_clicks = Some(core::mem::take(&mut self.click_count));
+                    self.last_release_at = None;
+                }
+            }
+        } else {
+            self.click_count = self.click_count.saturating_add(1);
+            self.last_release_at = Some(changed_at);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<P, S, C, const N: usize> PinSwitch<P, S, C, N>
+where
+    P: InputPin + embedded_hal_async::digital::Wait,
+    S: switch_state::PressedState,
+    C: Clock,
+{
+    /// Asynchronously waits for the next switch event
+    ///
+    /// Yields to the executor until the pin reports an edge instead of busy
+    /// polling like [`Switch::wait`]. While a debounce window, an auto-repeat,
+    /// a click sequence, the stuck detection or the confirmation is pending, the switch is re-polled every
+    /// millisecond using the passed in `delay` so that the time based
+    /// events are not missed.
+    ///
+    /// Available with the `async` feature
+    pub async fn wait_event<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        clock: &C,
+        delay: &mut D,
+    ) -> Result<SwitchEvent<C>, P::Error> {
+        loop {
+            if let Some(event) = self.poll(clock.try_now().unwrap())? {
+                return Ok(event);
+            }
+
+            let has_timed_work = self.pending_since.is_some()
+                || self.last_release_at.is_some()
+                || (self.is_pressed() && self.repeat_interval.is_some())
+                || (self.stuck_threshold.is_some()
+                    && self.is_pressed()
+                    && !self.flags.contains(SwitchFlags::STUCK))
+                || (self.confirm_threshold.is_some()
+                    && self.is_pressed()
+                    && !self.flags.contains(SwitchFlags::CONFIRMED));
+            if has_timed_work {
+                delay.delay_ms(1).await;
+            } else {
+                self.pin.wait_for_any_edge().await?;
+            }
         }
+    }
+}
+
+impl<P: InputPin, S: switch_state::PressedState, C: Clock, const N: usize> Switch<C>
+    for PinSwitch<P, S, C, N>
+{
+    type Error = P::Error;
 
-        self.is_pressed = new_state;
-        self.has_changed = true;
-        self.prev_state_lasted = self.current_state(now);
-        self.last_change_at = now;
+    fn poll(&mut self, now: Instant<C>) -> Result<Option<SwitchEvent<C>>, Self::Error> {
+        let is_high = self.pin.is_high()?;
+        let event = self.poll_raw(is_high, now);
+        self.flags.insert(SwitchFlags::PIN_READ);
+        Ok(event)
     }
 
     fn has_changed(&self) -> bool {
-        self.has_changed
+        self.flags.contains(SwitchFlags::CHANGED)
+    }
+
+    fn clear_changed(&mut self) {
+        self.flags.remove(SwitchFlags::CHANGED);
     }
 
     fn is_pressed(&self) -> bool {
-        self.is_pressed
+        self.flags.contains(SwitchFlags::PRESSED)
     }
 
     fn is_released(&self) -> bool {
-        !self.is_pressed
+        !self.is_pressed()
     }
 
     fn pressed_for(&self) -> Option<Milliseconds<C::T>> {
-        if !self.is_pressed {
+        if !self.is_pressed() {
             return Some(self.prev_state_lasted);
         }
         None
     }
 
     fn released_for(&self) -> Option<Milliseconds<C::T>> {
-        if self.is_pressed {
+        if self.is_pressed() {
             return Some(self.prev_state_lasted);
         }
         None
     }
 
-    fn wait(&mut self, clock: &C) {
+    fn wait(&mut self, clock: &C) -> Result<(), Self::Error> {
         loop {
-            self.poll(clock.try_now().unwrap());
-            if self.has_changed {
-                break;
+            if let Ok(now) = clock.try_now() {
+                self.poll(now)?;
+            }
+            if self.take_changed() {
+                return Ok(());
             }
         }
     }
@@ -174,8 +1252,17 @@ impl<P: InputPin, S: switch_state::PressedState, C: Clock> Switch<C> for PinSwit
     fn reset(&mut self) {
         self.last_change_at = Instant::<C>::new(C::T::from(0));
         self.prev_state_lasted = Milliseconds::<C::T>::new(C::T::from(0));
-        self.has_changed = false;
-        self.is_pressed = false;
+        self.flags = SwitchFlags::empty();
+        self.flags.set(SwitchFlags::PRESSED, self.default_pressed);
+        self.pending_since = None;
+        self.pending_samples = 0;
+        self.click_count = 0;
+        self.last_release_at = None;
+        self.finished_clicks = None;
+        self.events.clear();
+        if !self.keep_counters {
+            self.reset_counters();
+        }
     }
 
     fn prev_state_lasted_for(&self) -> Milliseconds<<C as Clock>::T> {
@@ -183,10 +1270,115 @@ impl<P: InputPin, S: switch_state::PressedState, C: Clock> Switch<C> for PinSwit
     }
 
     fn current_state(&self, now: Instant<C>) -> Milliseconds<<C as Clock>::T> {
-        now
-            .checked_duration_since(&self.last_change_at)
-            .unwrap()
-            .try_into()
-            .unwrap()
+        elapsed_since(now, self.last_change_at)
+    }
+}
+
+impl<P: InputPin, S: switch_state::PressedState, C: Clock, const N: usize> Poll<C>
+    for PinSwitch<P, S, C, N>
+{
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        Switch::poll(self, now).map(|_| ()).map_err(|_| PollError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::switch_state::PressedOnHigh;
+    use super::{PinSwitch, Switch, SwitchEvent};
+    use crate::mock::{MockClock, MockInputPin, MockPin};
+    use crate::time::Milliseconds;
+
+    type TestSwitch<'a> = PinSwitch<MockInputPin<'a>, PressedOnHigh, MockClock>;
+
+    #[test]
+    fn wait_timeout_bails_out_on_clock_error() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut switch = TestSwitch::new(pin.input());
+
+        clock.set_failing(true);
+        assert_eq!(switch.wait_timeout(&clock, Milliseconds(100)), Ok(false));
+    }
+
+    #[test]
+    fn change_stays_latched_until_taken() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut switch = TestSwitch::new(pin.input());
+
+        pin.set_high(true);
+        switch.poll(clock.now()).unwrap();
+        clock.advance(10);
+        assert!(matches!(switch.poll(clock.now()), Ok(None)));
+
+        assert!(switch.take_changed());
+        assert!(!switch.take_changed());
+    }
+
+    #[test]
+    fn buffers_all_events_of_a_single_poll() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut switch = TestSwitch::new(pin.input())
+            .with_stuck_threshold(Milliseconds(1_999))
+            .with_repeat(Milliseconds(2_000), Milliseconds(500));
+
+        pin.set_high(true);
+        let event = switch.poll(clock.now());
+        assert!(matches!(event, Ok(Some(SwitchEvent::Pressed))));
+
+        // the switch becomes stuck and fires the auto-repeat within the same poll
+        clock.advance(2_000);
+        let event = switch.poll(clock.now());
+        assert!(matches!(event, Ok(Some(SwitchEvent::Fault))));
+
+        let mut events = switch.drain_events();
+        assert!(matches!(events.next(), Some(SwitchEvent::Pressed)));
+        assert!(matches!(events.next(), Some(SwitchEvent::Fault)));
+        assert!(matches!(events.next(), Some(SwitchEvent::Repeat)));
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn normally_closed_switch_reports_no_edge_on_startup() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        // the normally closed contact rests in the pressed state
+        pin.set_high(true);
+        let mut switch = TestSwitch::new(pin.input()).with_default_pressed(true);
+
+        for _ in 0..2 {
+            assert!(matches!(switch.poll(clock.now()), Ok(None)));
+            assert!(switch.is_pressed());
+            assert!(!switch.take_changed());
+            clock.advance(10);
+            switch.reset();
+        }
+        assert!(switch.drain_events().next().is_none());
+    }
+
+    #[test]
+    fn confirmed_does_not_replace_repeat_at_the_same_instant() {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut switch = TestSwitch::new(pin.input())
+            .with_repeat(Milliseconds(500), Milliseconds(500))
+            .with_confirm_threshold(Milliseconds(2_000));
+
+        pin.set_high(true);
+        assert_eq!(switch.poll(clock.now()), Ok(Some(SwitchEvent::Pressed)));
+        for _ in 0..3 {
+            clock.advance(500);
+            assert_eq!(switch.poll(clock.now()), Ok(Some(SwitchEvent::Repeat)));
+        }
+
+        // the fourth repeat falls onto the confirm threshold
+        clock.advance(500);
+        assert_eq!(switch.poll(clock.now()), Ok(Some(SwitchEvent::Repeat)));
+        assert_eq!(switch.confirm_progress(clock.now()), 1 << 16);
+
+        let events = switch.drain_events().skip(4);
+        assert!(events.eq([SwitchEvent::Repeat, SwitchEvent::Confirmed]));
     }
 }
@@ -0,0 +1,224 @@
+use super::{ReleaseKind, Switch, SwitchEvent};
+use crate::analog::AnalogChannel;
+use crate::poll::{Poll, PollError};
+use crate::time::{elapsed_since, Clock, Instant, Milliseconds};
+
+/// Switch implementation for an [`AnalogChannel`]
+///
+/// Derives the pressed state from the analog samples (resistor ladders, hall
+/// sensors, etc.) with Schmitt trigger behavior. The switch gets pressed once the
+/// sample crosses the press threshold and released once it crosses back the
+/// release threshold, so the noise around a single threshold doesn't cause chatter.
+pub struct AnalogSwitch<CH: AnalogChannel, C: Clock> {
+    channel: CH,
+    press_threshold: u16,
+    release_threshold: u16,
+    is_pressed: bool,
+    has_changed: bool,
+    last_change_at: Instant<C>,
+    prev_state_lasted: Milliseconds<C::T>,
+}
+
+impl<CH: AnalogChannel, C: Clock> AnalogSwitch<CH, C> {
+    /// Create new [`AnalogSwitch`] instance for the passed in `channel`
+    ///
+    /// The switch is pressed when the sample rises to `threshold` and released when
+    /// it falls below `threshold - hysteresis`.
+    pub fn new(channel: CH, threshold: u16, hysteresis: u16) -> Self {
+        let mut switch = Self {
+            channel,
+            press_threshold: 0,
+            release_threshold: 0,
+            is_pressed: false,
+            has_changed: false,
+            last_change_at: Instant::<C>::new(C::T::from(0)),
+            prev_state_lasted: Milliseconds::<C::T>::new(C::T::from(0)),
+        };
+        switch.set_thresholds(threshold, threshold.saturating_sub(hysteresis));
+        switch
+    }
+
+    /// Sets separate press and release thresholds
+    ///
+    /// When `press` is above `release`, the switch is pressed when the sample rises
+    /// to `press` and released when it falls below `release`.
+    ///
+    /// When `press` is below `release`, the behavior is inverted, the switch is pressed
+    /// when the sample falls to `press` and released when it rises above `release`.
+    pub fn set_thresholds(&mut self, press: u16, release: u16) {
+        self.press_threshold = press;
+        self.release_threshold = release;
+    }
+
+    /// Returns the press and release thresholds
+    pub fn thresholds(&self) -> (u16, u16) {
+        (self.press_threshold, self.release_threshold)
+    }
+
+    /// Derives the new pressed state from the sample
+    fn pressed_state(&self, sample: u16) -> bool {
+        let (press, release) = (self.press_threshold, self.release_threshold);
+        if press >= release {
+            if self.is_pressed {
+                sample >= release
+            } else {
+                sample >= press
+            }
+        } else if self.is_pressed {
+            sample <= release
+        } else {
+            sample <= press
+        }
+    }
+}
+
+impl<CH: AnalogChannel, C: Clock> Switch<C> for AnalogSwitch<CH, C> {
+    type Error = CH::Error;
+
+    fn reset(&mut self) {
+        self.last_change_at = Instant::<C>::new(C::T::from(0));
+        self.prev_state_lasted = Milliseconds::<C::T>::new(C::T::from(0));
+        self.has_changed = false;
+        self.is_pressed = false;
+    }
+
+    fn poll(&mut self, now: Instant<C>) -> Result<Option<SwitchEvent<C>>, Self::Error> {
+        let sample = self.channel.read()?;
+        let new_state = self.pressed_state(sample);
+
+        if new_state == self.is_pressed {
+            return Ok(None);
+        }
+
+        self.is_pressed = new_state;
+        self.has_changed = true;
+        self.prev_state_lasted = self.current_state(now);
+        self.last_change_at = now;
+
+        if self.is_pressed {
+            return Ok(Some(SwitchEvent::Pressed));
+        }
+        Ok(Some(SwitchEvent::Released {
+            held_for: self.prev_state_lasted,
+            kind: ReleaseKind::Tap,
+        }))
+    }
+
+    fn has_changed(&self) -> bool {
+        self.has_changed
+    }
+
+    fn clear_changed(&mut self) {
+        self.has_changed = false;
+    }
+
+    fn is_pressed(&self) -> bool {
+        self.is_pressed
+    }
+
+    fn is_released(&self) -> bool {
+        !self.is_pressed
+    }
+
+    fn pressed_for(&self) -> Option<Milliseconds<C::T>> {
+        if !self.is_pressed {
+            return Some(self.prev_state_lasted);
+        }
+        None
+    }
+
+    fn released_for(&self) -> Option<Milliseconds<C::T>> {
+        if self.is_pressed {
+            return Some(self.prev_state_lasted);
+        }
+        None
+    }
+
+    fn prev_state_lasted_for(&self) -> Milliseconds<C::T> {
+        self.prev_state_lasted
+    }
+
+    fn current_state(&self, now: Instant<C>) -> Milliseconds<C::T> {
+        elapsed_since(now, self.last_change_at)
+    }
+
+    fn wait(&mut self, clock: &C) -> Result<(), Self::Error> {
+        loop {
+            if let Ok(now) = clock.try_now() {
+                self.poll(now)?;
+            }
+            if self.take_changed() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<CH: AnalogChannel, C: Clock> Poll<C> for AnalogSwitch<CH, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        Switch::poll(self, now).map(|_| ()).map_err(|_| PollError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    use super::AnalogSwitch;
+    use crate::analog::AnalogChannel;
+    use crate::mock::MockClock;
+    use crate::switch::{Switch, SwitchEvent};
+
+    /// Channel reading the sample set by the test
+    struct StubChannel<'a>(&'a Cell<u16>);
+
+    impl AnalogChannel for StubChannel<'_> {
+        type Error = Infallible;
+
+        fn read(&mut self) -> Result<u16, Self::Error> {
+            Ok(self.0.get())
+        }
+    }
+
+    /// Feeds the samples to the switch, returning the number of presses and releases
+    fn count_edges(
+        switch: &mut AnalogSwitch<StubChannel, MockClock>,
+        sample: &Cell<u16>,
+        samples: &[u16],
+    ) -> (u32, u32) {
+        let clock = MockClock::new();
+        let (mut presses, mut releases) = (0, 0);
+        for value in samples {
+            sample.set(*value);
+            match switch.poll(clock.now()) {
+                Ok(Some(SwitchEvent::Pressed)) => presses += 1,
+                Ok(Some(SwitchEvent::Released { .. })) => releases += 1,
+                _ => {}
+            }
+            clock.advance(10);
+        }
+        (presses, releases)
+    }
+
+    #[test]
+    fn noise_between_the_thresholds_does_not_chatter() {
+        let sample = Cell::new(0);
+        let mut switch = AnalogSwitch::new(StubChannel(&sample), 600, 200);
+
+        let samples = [100, 550, 620, 590, 450, 420, 610, 380, 450, 590];
+        assert_eq!(count_edges(&mut switch, &sample, &samples), (1, 1));
+        assert!(!switch.is_pressed());
+    }
+
+    #[test]
+    fn inverted_thresholds_press_on_the_falling_sample() {
+        let sample = Cell::new(0);
+        let mut switch = AnalogSwitch::new(StubChannel(&sample), 0, 0);
+        switch.set_thresholds(400, 600);
+
+        let samples = [900, 450, 380, 420, 550, 590, 390, 620, 550, 410];
+        assert_eq!(count_edges(&mut switch, &sample, &samples), (1, 1));
+        assert!(!switch.is_pressed());
+    }
+}
@@ -0,0 +1,246 @@
+use embedded_hal::digital::InputPin;
+
+use super::{switch_state, PinSwitch};
+use crate::time::{Clock, Milliseconds};
+
+/// Invalid configuration rejected by the [`PinSwitchBuilder::build`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SwitchConfigError {
+    /// Auto-repeat interval is shorter than the press debounce window, the repeats
+    /// would fire faster than a press could be told apart from a bounce
+    RepeatBelowDebounce,
+    /// Long press threshold is within the press debounce window, every press
+    /// would be reported as a long press
+    LongPressBelowDebounce,
+    /// Stuck threshold is not above the long press threshold, the long presses
+    /// would be reported as faults
+    StuckBelowLongPress,
+}
+
+/// Builder of the fully configured [`PinSwitch`]
+///
+/// Collects the configuration and validates it as a whole in [build](#method.build),
+/// so the conflicting settings are caught before the switch is put to use.
+/// See the setters of the [`PinSwitch`] for the meaning of the individual settings.
+pub struct PinSwitchBuilder<C: Clock> {
+    polarity: Option<switch_state::Polarity>,
+    debounce: (Milliseconds<C::T>, Milliseconds<C::T>),
+    sample_debounce: u8,
+    click_timeout: Option<Milliseconds<C::T>>,
+    repeat_delay: Milliseconds<C::T>,
+    repeat_interval: Option<Milliseconds<C::T>>,
+    long_press_threshold: Option<Milliseconds<C::T>>,
+    stuck_threshold: Option<Milliseconds<C::T>>,
+    confirm_threshold: Option<Milliseconds<C::T>>,
+    default_pressed: bool,
+    keep_counters: bool,
+}
+
+impl<C: Clock> PinSwitchBuilder<C> {
+    /// Create new [`PinSwitchBuilder`] with the defaults of [`PinSwitch::new`]
+    pub fn new() -> Self {
+        let zero = Milliseconds::<C::T>::new(C::T::from(0));
+        Self {
+            polarity: None,
+            debounce: (zero, zero),
+            sample_debounce: 0,
+            click_timeout: None,
+            repeat_delay: zero,
+            repeat_interval: None,
+            long_press_threshold: None,
+            stuck_threshold: None,
+            confirm_threshold: None,
+            default_pressed: false,
+            keep_counters: false,
+        }
+    }
+
+    /// Sets the pressed polarity, see [`PinSwitch::set_polarity`]
+    pub fn with_polarity(mut self, polarity: switch_state::Polarity) -> Self {
+        self.polarity = Some(polarity);
+        self
+    }
+
+    /// Sets the debounce window of both edges, see [`PinSwitch::set_debounce`]
+    pub fn with_debounce(self, debounce: Milliseconds<C::T>) -> Self {
+        self.with_debounce_windows(debounce, debounce)
+    }
+
+    /// Sets the separate press and release debounce windows,
+    /// see [`PinSwitch::set_debounce_windows`]
+    pub fn with_debounce_windows(
+        mut self,
+        press: Milliseconds<C::T>,
+        release: Milliseconds<C::T>,
+    ) -> Self {
+        self.debounce = (press, release);
+        self
+    }
+
+    /// Sets the number of consecutive samples required to accept a state change,
+    /// see [`PinSwitch::set_sample_debounce`]
+    pub fn with_sample_debounce(mut self, samples: u8) -> Self {
+        self.sample_debounce = samples;
+        self
+    }
+
+    /// Enables the click counting, see [`PinSwitch::set_click_timeout`]
+    pub fn with_click_timeout(mut self, timeout: Milliseconds<C::T>) -> Self {
+        self.click_timeout = Some(timeout);
+        self
+    }
+
+    /// Enables the auto-repeat, see [`PinSwitch::set_repeat`]
+    pub fn with_repeat(
+        mut self,
+        initial_delay: Milliseconds<C::T>,
+        interval: Milliseconds<C::T>,
+    ) -> Self {
+        self.repeat_delay = initial_delay;
+        self.repeat_interval = Some(interval);
+        self
+    }
+
+    /// Sets the long press threshold, see [`PinSwitch::set_long_press_threshold`]
+    pub fn with_long_press_threshold(mut self, threshold: Milliseconds<C::T>) -> Self {
+        self.long_press_threshold = Some(threshold);
+        self
+    }
+
+    /// Enables the stuck detection, see [`PinSwitch::set_stuck_threshold`]
+    pub fn with_stuck_threshold(mut self, threshold: Milliseconds<C::T>) -> Self {
+        self.stuck_threshold = Some(threshold);
+        self
+    }
+
+    /// Enables the hold to confirm, see [`PinSwitch::set_confirm_threshold`]
+    pub fn with_confirm_threshold(mut self, threshold: Milliseconds<C::T>) -> Self {
+        self.confirm_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets the initial pressed state, see [`PinSwitch::set_default_pressed`]
+    pub fn with_default_pressed(mut self, is_pressed: bool) -> Self {
+        self.default_pressed = is_pressed;
+        self
+    }
+
+    /// Keeps the counters across the resets, see [`PinSwitch::set_keep_counters`]
+    pub fn with_keep_counters(mut self, keep_counters: bool) -> Self {
+        self.keep_counters = keep_counters;
+        self
+    }
+
+    /// Checks that the settings don't conflict with each other
+    ///
+    /// Returns the first conflict found
+    pub fn validate(&self) -> Result<(), SwitchConfigError> {
+        let (press_debounce, _) = self.debounce;
+        if self
+            .repeat_interval
+            .is_some_and(|interval| interval < press_debounce)
+        {
+            return Err(SwitchConfigError::RepeatBelowDebounce);
+        }
+        if let Some(long_press) = self.long_press_threshold {
+            if long_press <= press_debounce {
+                return Err(SwitchConfigError::LongPressBelowDebounce);
+            }
+            if self
+                .stuck_threshold
+                .is_some_and(|stuck| stuck <= long_press)
+            {
+                return Err(SwitchConfigError::StuckBelowLongPress);
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the configuration and builds the switch for the passed in `pin`
+    ///
+    /// Returns an error if the settings conflict, see [validate](#method.validate)
+    pub fn build<P: InputPin, S: switch_state::PressedState, const N: usize>(
+        self,
+        pin: P,
+    ) -> Result<PinSwitch<P, S, C, N>, SwitchConfigError> {
+        self.validate()?;
+
+        let mut switch = PinSwitch::new(pin);
+        switch.set_polarity(self.polarity);
+        switch.set_debounce_windows(self.debounce.0, self.debounce.1);
+        switch.set_sample_debounce(self.sample_debounce);
+        switch.set_click_timeout(self.click_timeout);
+        if let Some(interval) = self.repeat_interval {
+            switch.set_repeat(self.repeat_delay, interval);
+        }
+        switch.set_long_press_threshold(self.long_press_threshold);
+        switch.set_stuck_threshold(self.stuck_threshold);
+        switch.set_confirm_threshold(self.confirm_threshold);
+        switch.set_default_pressed(self.default_pressed);
+        switch.set_keep_counters(self.keep_counters);
+        Ok(switch)
+    }
+}
+
+impl<C: Clock> Default for PinSwitchBuilder<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PinSwitchBuilder, SwitchConfigError};
+    use crate::mock::{MockClock, MockInputPin, MockPin};
+    use crate::switch::switch_state::PressedOnHigh;
+    use crate::switch::PinSwitch;
+    use crate::time::Milliseconds;
+
+    type TestSwitch<'a> = PinSwitch<MockInputPin<'a>, PressedOnHigh, MockClock>;
+
+    fn debounced() -> PinSwitchBuilder<MockClock> {
+        PinSwitchBuilder::new().with_debounce(Milliseconds(20))
+    }
+
+    #[test]
+    fn rejects_repeat_below_debounce() {
+        let builder = debounced().with_repeat(Milliseconds(500), Milliseconds(10));
+        assert_eq!(
+            builder.validate(),
+            Err(SwitchConfigError::RepeatBelowDebounce)
+        );
+    }
+
+    #[test]
+    fn rejects_long_press_within_debounce() {
+        let builder = debounced().with_long_press_threshold(Milliseconds(20));
+        assert_eq!(
+            builder.validate(),
+            Err(SwitchConfigError::LongPressBelowDebounce)
+        );
+    }
+
+    #[test]
+    fn rejects_stuck_threshold_not_above_long_press() {
+        let builder = debounced()
+            .with_long_press_threshold(Milliseconds(1_000))
+            .with_stuck_threshold(Milliseconds(1_000));
+        assert_eq!(
+            builder.validate(),
+            Err(SwitchConfigError::StuckBelowLongPress)
+        );
+    }
+
+    #[test]
+    fn builds_a_consistent_configuration() {
+        let pin = MockPin::new();
+        let switch: Result<TestSwitch, _> = debounced()
+            .with_repeat(Milliseconds(500), Milliseconds(100))
+            .with_long_press_threshold(Milliseconds(1_000))
+            .with_stuck_threshold(Milliseconds(10_000))
+            .build(pin.input());
+        let windows = switch.map(|switch| switch.debounce_windows());
+        assert_eq!(windows, Ok((Milliseconds(20), Milliseconds(20))));
+    }
+}
@@ -0,0 +1,96 @@
+use super::Switch;
+use crate::time::{Clock, Instant, Milliseconds};
+
+/// Chord (combo) detector of two switches held at the same time
+///
+/// The combo doesn't own the switches, they are polled by the user as usual
+/// and passed in to [poll](#method.poll) by reference, so each of them can still
+/// be used on its own as well.
+///
+/// Optional simultaneity window requires both switches to be pressed within
+/// the window of each other, e.g. to tell a reset combo apart from a press of
+/// the second button while the first one is held for another purpose.
+pub struct Combo<C: Clock> {
+    window: Option<Milliseconds<C::T>>,
+    is_active: bool,
+    has_activated: bool,
+}
+
+impl<C: Clock> Combo<C> {
+    /// Create new [`Combo`] instance without the simultaneity window
+    pub fn new() -> Self {
+        Self {
+            window: None,
+            is_active: false,
+            has_activated: false,
+        }
+    }
+
+    /// Sets the simultaneity window
+    ///
+    /// See [set_window](#method.set_window)
+    pub fn with_window(mut self, window: Milliseconds<C::T>) -> Self {
+        self.set_window(Some(window));
+        self
+    }
+
+    /// Sets the simultaneity window
+    ///
+    /// The combo is satisfied only if the switches have been pressed at most
+    /// `window` apart. [`None`] (default) accepts the presses regardless of their timing.
+    pub fn set_window(&mut self, window: Option<Milliseconds<C::T>>) {
+        self.window = window;
+    }
+
+    /// Indicates that the combo is satisfied, i.e. both switches are held
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    /// Indicates that the combo has become satisfied during the last poll
+    pub fn has_activated(&self) -> bool {
+        self.has_activated
+    }
+
+    /// Resets the combo to the inactive state
+    pub fn reset(&mut self) {
+        self.is_active = false;
+        self.has_activated = false;
+    }
+
+    /// Updates the combo state from the switches `a` and `b`
+    ///
+    /// Should be called after polling both of the switches. Once satisfied, the combo
+    /// stays active until either of the switches is released.
+    ///
+    /// Returns `true` only on the poll the combo has become satisfied
+    pub fn poll<A: Switch<C>, B: Switch<C>>(&mut self, a: &A, b: &B, now: Instant<C>) -> bool {
+        let is_held = a.is_pressed() && b.is_pressed();
+        let was_active = self.is_active;
+
+        self.is_active = is_held && (was_active || self.is_simultaneous(a, b, now));
+        self.has_activated = self.is_active && !was_active;
+        self.has_activated
+    }
+
+    /// Checks that the presses of the held switches fit into the simultaneity window
+    fn is_simultaneous<A: Switch<C>, B: Switch<C>>(&self, a: &A, b: &B, now: Instant<C>) -> bool {
+        let Some(window) = self.window else {
+            return true;
+        };
+
+        let (held_a, held_b) = (a.current_state(now), b.current_state(now));
+        let apart = if held_a > held_b {
+            held_a - held_b
+        } else {
+            held_b - held_a
+        };
+        apart <= window
+    }
+}
+
+impl<C: Clock> Default for Combo<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,285 @@
+use core::marker::PhantomData;
+
+use super::{Switch, SwitchEvent};
+use crate::poll::{Poll, PollError};
+#[cfg(feature = "defmt")]
+use crate::time::FixedPoint;
+use crate::time::{elapsed_since, Clock, Instant, Milliseconds};
+
+/// Default longest press still recognized as a tap
+const DEFAULT_TAP_MAX_MS: u32 = 250;
+/// Default longest pause between the taps of a double tap
+const DEFAULT_DOUBLE_GAP_MS: u32 = 300;
+/// Default shortest press recognized as a hold
+const DEFAULT_HOLD_MIN_MS: u32 = 800;
+
+/// Gesture reported by the [`GestureRecognizer`]
+#[derive(Debug)]
+pub enum Gesture<C: Clock> {
+    /// Single short press not followed by another one within the double tap gap
+    Tap,
+    /// Two short presses within the double tap gap
+    DoubleTap,
+    /// Switch has been held for the hold threshold, reported while still held
+    Hold,
+    /// Held switch has been released after being held for `held_for`
+    HoldRelease { held_for: Milliseconds<C::T> },
+}
+
+// manual impls, the derives would require the clock itself to be `Copy` or `PartialEq`
+impl<C: Clock> Clone for Gesture<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Clock> Copy for Gesture<C> {}
+
+impl<C: Clock> PartialEq for Gesture<C> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Gesture::HoldRelease { held_for },
+                Gesture::HoldRelease {
+                    held_for: other_held_for,
+                },
+            ) => held_for == other_held_for,
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+impl<C: Clock> Eq for Gesture<C> {}
+
+#[cfg(feature = "defmt")]
+impl<C: Clock> defmt::Format for Gesture<C>
+where
+    C::T: defmt::Format,
+{
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Gesture::Tap => defmt::write!(f, "Tap"),
+            Gesture::DoubleTap => defmt::write!(f, "DoubleTap"),
+            Gesture::Hold => defmt::write!(f, "Hold"),
+            Gesture::HoldRelease { held_for } => {
+                defmt::write!(f, "HoldRelease {{ held_for: {}ms }}", held_for.integer())
+            }
+        }
+    }
+}
+
+/// State of the gesture recognition
+enum GestureState<C: Clock> {
+    /// Waiting for a press
+    Idle,
+    /// Switch is pressed, `is_second` is set for the press following a tap
+    Pressed { is_second: bool },
+    /// Tap has been released at the instant, waiting for the second one
+    Tapped(Instant<C>),
+    /// Hold has been reported, waiting for the release
+    Holding,
+}
+
+impl<C: Clock> Clone for GestureState<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Clock> Copy for GestureState<C> {}
+
+/// Single button gesture recognizer wrapping a [`Switch`]
+///
+/// Tells the taps, double taps and holds apart with a single state machine. Each
+/// interaction reports exactly one [`Gesture::Tap`] or [`Gesture::DoubleTap`], or
+/// a [`Gesture::Hold`] once the hold threshold is reached followed by
+/// a [`Gesture::HoldRelease`] on the release. A hold never reports a tap.
+///
+/// The edge cases degrade as follows:
+/// - a press longer than a tap, but released before the hold threshold, reports
+///   nothing on its own and ends the interaction, the tap preceding it is
+///   reported as a [`Gesture::Tap`]
+/// - a tap followed by a hold reports just the hold
+/// - a triple tap reports a [`Gesture::DoubleTap`] followed by a [`Gesture::Tap`]
+pub struct GestureRecognizer<S: Switch<C>, C: Clock> {
+    switch: S,
+    tap_max: Milliseconds<C::T>,
+    double_gap: Milliseconds<C::T>,
+    hold_min: Milliseconds<C::T>,
+    state: GestureState<C>,
+    clock: PhantomData<C>,
+}
+
+impl<S: Switch<C>, C: Clock> GestureRecognizer<S, C> {
+    /// Create new [`GestureRecognizer`] wrapping the passed in `switch`
+    ///
+    /// The thresholds default to 250 ms tap, 300 ms double tap gap and 800 ms hold,
+    /// see [set_thresholds](#method.set_thresholds)
+    pub fn new(switch: S) -> Self {
+        Self {
+            switch,
+            tap_max: Milliseconds::<C::T>::new(C::T::from(DEFAULT_TAP_MAX_MS)),
+            double_gap: Milliseconds::<C::T>::new(C::T::from(DEFAULT_DOUBLE_GAP_MS)),
+            hold_min: Milliseconds::<C::T>::new(C::T::from(DEFAULT_HOLD_MIN_MS)),
+            state: GestureState::Idle,
+            clock: PhantomData,
+        }
+    }
+
+    /// Sets the gesture thresholds
+    ///
+    /// See [set_thresholds](#method.set_thresholds)
+    pub fn with_thresholds(
+        mut self,
+        tap_max: Milliseconds<C::T>,
+        double_gap: Milliseconds<C::T>,
+        hold_min: Milliseconds<C::T>,
+    ) -> Self {
+        self.set_thresholds(tap_max, double_gap, hold_min);
+        self
+    }
+
+    /// Sets the gesture thresholds
+    ///
+    /// Presses up to `tap_max` long are taps, two taps at most `double_gap` apart
+    /// form a double tap, and presses at least `hold_min` long are holds. A single
+    /// tap is reported once the `double_gap` has passed without another press.
+    pub fn set_thresholds(
+        &mut self,
+        tap_max: Milliseconds<C::T>,
+        double_gap: Milliseconds<C::T>,
+        hold_min: Milliseconds<C::T>,
+    ) {
+        self.tap_max = tap_max;
+        self.double_gap = double_gap;
+        self.hold_min = hold_min;
+    }
+
+    /// Resets the wrapped switch and drops the gesture in progress
+    pub fn reset(&mut self) {
+        self.switch.reset();
+        self.state = GestureState::Idle;
+    }
+
+    /// Polls the wrapped switch, advancing the gesture recognition
+    ///
+    /// Returns the gesture recognized during this poll, if any
+    pub fn poll(&mut self, now: Instant<C>) -> Result<Option<Gesture<C>>, S::Error> {
+        let event = self.switch.poll(now)?;
+
+        let (state, gesture) = match (self.state, event) {
+            (GestureState::Idle, Some(SwitchEvent::Pressed)) => {
+                (GestureState::Pressed { is_second: false }, None)
+            }
+            (GestureState::Tapped(at), Some(SwitchEvent::Pressed))
+                if elapsed_since(now, at) > self.double_gap =>
+            {
+                (
+                    GestureState::Pressed { is_second: false },
+                    Some(Gesture::Tap),
+                )
+            }
+            (GestureState::Tapped(_), Some(SwitchEvent::Pressed)) => {
+                (GestureState::Pressed { is_second: true }, None)
+            }
+            (GestureState::Tapped(at), _) if elapsed_since(now, at) > self.double_gap => {
+                (GestureState::Idle, Some(Gesture::Tap))
+            }
+            (GestureState::Pressed { is_second }, Some(SwitchEvent::Released { held_for, .. })) => {
+                match (held_for <= self.tap_max, is_second) {
+                    (true, false) => (GestureState::Tapped(now), None),
+                    (true, true) => (GestureState::Idle, Some(Gesture::DoubleTap)),
+                    (false, true) => (GestureState::Idle, Some(Gesture::Tap)),
+                    (false, false) => (GestureState::Idle, None),
+                }
+            }
+            (GestureState::Pressed { .. }, _)
+                if self.switch.current_state(now) >= self.hold_min =>
+            {
+                (GestureState::Holding, Some(Gesture::Hold))
+            }
+            (GestureState::Holding, Some(SwitchEvent::Released { held_for, .. })) => {
+                (GestureState::Idle, Some(Gesture::HoldRelease { held_for }))
+            }
+            (state, _) => (state, None),
+        };
+
+        self.state = state;
+        Ok(gesture)
+    }
+
+    /// Returns a reference to the wrapped switch
+    pub fn inner(&self) -> &S {
+        &self.switch
+    }
+
+    /// Releases the wrapped switch
+    pub fn into_inner(self) -> S {
+        self.switch
+    }
+}
+
+impl<S: Switch<C>, C: Clock> Poll<C> for GestureRecognizer<S, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now).map(|_| ()).map_err(|_| PollError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gesture, GestureRecognizer};
+    use crate::mock::{MockClock, MockInputPin, MockPin};
+    use crate::switch::switch_state::PressedOnHigh;
+    use crate::switch::PinSwitch;
+
+    type TestRecognizer<'a> =
+        GestureRecognizer<PinSwitch<MockInputPin<'a>, PressedOnHigh, MockClock>, MockClock>;
+
+    /// Holds each pin level of the `script` for the given milliseconds, polling
+    /// every 10 ms, and returns the recognized gestures
+    fn run(script: &[(bool, u32)]) -> Vec<Gesture<MockClock>> {
+        let clock = MockClock::new();
+        let pin = MockPin::new();
+        let mut recognizer = TestRecognizer::new(PinSwitch::new(pin.input()));
+
+        let mut gestures = Vec::new();
+        for (is_high, duration) in script {
+            pin.set_high(*is_high);
+            for _ in 0..duration / 10 {
+                gestures.extend(recognizer.poll(clock.now()).unwrap());
+                clock.advance(10);
+            }
+        }
+        gestures
+    }
+
+    #[test]
+    fn hold_never_reports_a_tap() {
+        let gestures = run(&[(true, 1_000), (false, 500)]);
+        assert_eq!(gestures.len(), 2);
+        assert_eq!(gestures[0], Gesture::Hold);
+        assert!(matches!(gestures[1], Gesture::HoldRelease { .. }));
+    }
+
+    #[test]
+    fn tap_followed_by_a_hold_reports_just_the_hold() {
+        let gestures = run(&[(true, 100), (false, 100), (true, 1_000), (false, 500)]);
+        assert_eq!(gestures.len(), 2);
+        assert_eq!(gestures[0], Gesture::Hold);
+        assert!(matches!(gestures[1], Gesture::HoldRelease { .. }));
+    }
+
+    #[test]
+    fn triple_tap_reports_a_double_tap_and_a_tap() {
+        let tap = [(true, 100), (false, 100)];
+        let gestures = run(&[tap, tap, tap, [(false, 500); 2]].concat());
+        assert_eq!(gestures, [Gesture::DoubleTap, Gesture::Tap]);
+    }
+
+    #[test]
+    fn long_press_short_of_a_hold_ends_the_interaction() {
+        assert_eq!(run(&[(true, 500), (false, 500)]), []);
+        let gestures = run(&[(true, 100), (false, 100), (true, 500), (false, 500)]);
+        assert_eq!(gestures, [Gesture::Tap]);
+    }
+}
@@ -0,0 +1,68 @@
+use embedded_hal::digital::{ErrorType, InputPin};
+
+/// Input pin adapter negating the reads of the wrapped pin
+///
+/// Compensates for an inverting buffer in front of the input, so that it can be
+/// composed with the [`PinSwitch`](super::PinSwitch) or any other pin consumer.
+/// The inversion can be switched off at runtime, e.g. when it depends on
+/// the board revision read from a configuration.
+///
+/// Errors of the wrapped pin are passed through as is.
+pub struct Inverting<P: InputPin> {
+    pin: P,
+    is_inverted: bool,
+}
+
+impl<P: InputPin> Inverting<P> {
+    /// Create new [`Inverting`] adapter negating the reads of the passed in `pin`
+    pub fn new(pin: P) -> Self {
+        Self {
+            pin,
+            is_inverted: true,
+        }
+    }
+
+    /// Sets whether the reads are negated
+    ///
+    /// See [set_inverted](#method.set_inverted)
+    pub fn with_inverted(mut self, is_inverted: bool) -> Self {
+        self.set_inverted(is_inverted);
+        self
+    }
+
+    /// Sets whether the reads are negated
+    ///
+    /// The reads are negated by default, the pin is read as is otherwise.
+    pub fn set_inverted(&mut self, is_inverted: bool) {
+        self.is_inverted = is_inverted;
+    }
+
+    /// Indicates whether the reads are negated
+    pub fn is_inverted(&self) -> bool {
+        self.is_inverted
+    }
+
+    /// Returns a reference to the wrapped pin
+    pub fn inner(&self) -> &P {
+        &self.pin
+    }
+
+    /// Releases the wrapped pin
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+}
+
+impl<P: InputPin> ErrorType for Inverting<P> {
+    type Error = P::Error;
+}
+
+impl<P: InputPin> InputPin for Inverting<P> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.pin.is_high()? != self.is_inverted)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.pin.is_low()? != self.is_inverted)
+    }
+}
@@ -0,0 +1,83 @@
+use core::marker::PhantomData;
+
+use super::{Switch, SwitchEvent};
+use crate::poll::{Poll, PollError};
+use crate::time::{Clock, Instant};
+
+/// Latching (toggle) switch wrapper
+///
+/// Treats a momentary switch as a latch, each press of the wrapped switch flips
+/// the persistent latched state, which stays in place until the next press
+/// regardless of the physical switch state.
+pub struct LatchSwitch<S: Switch<C>, C: Clock> {
+    switch: S,
+    is_latched_on: bool,
+    has_toggled: bool,
+    clock: PhantomData<C>,
+}
+
+impl<S: Switch<C>, C: Clock> LatchSwitch<S, C> {
+    /// Create new [`LatchSwitch`] wrapping the passed in `switch`
+    ///
+    /// The latch starts in the off state
+    pub fn new(switch: S) -> Self {
+        Self {
+            switch,
+            is_latched_on: false,
+            has_toggled: false,
+            clock: PhantomData,
+        }
+    }
+
+    /// Indicates that the latch is in the on state
+    pub fn is_latched_on(&self) -> bool {
+        self.is_latched_on
+    }
+
+    /// Indicates that the latch has been toggled during the last poll
+    pub fn has_toggled(&self) -> bool {
+        self.has_toggled
+    }
+
+    /// Forces the latched state
+    pub fn set_latched(&mut self, is_on: bool) {
+        self.is_latched_on = is_on;
+    }
+
+    /// Resets the wrapped switch and returns the latch to the off state
+    pub fn reset(&mut self) {
+        self.switch.reset();
+        self.is_latched_on = false;
+        self.has_toggled = false;
+    }
+
+    /// Polls the wrapped switch, toggling the latch on each press
+    ///
+    /// Returns the event reported by the wrapped switch, see [`Switch::poll`]
+    pub fn poll(&mut self, now: Instant<C>) -> Result<Option<SwitchEvent<C>>, S::Error> {
+        let event = self.switch.poll(now)?;
+
+        self.has_toggled = matches!(event, Some(SwitchEvent::Pressed));
+        if self.has_toggled {
+            self.is_latched_on = !self.is_latched_on;
+        }
+
+        Ok(event)
+    }
+
+    /// Returns a reference to the wrapped switch
+    pub fn inner(&self) -> &S {
+        &self.switch
+    }
+
+    /// Releases the wrapped switch
+    pub fn into_inner(self) -> S {
+        self.switch
+    }
+}
+
+impl<S: Switch<C>, C: Clock> Poll<C> for LatchSwitch<S, C> {
+    fn poll_at(&mut self, now: Instant<C>) -> Result<(), PollError> {
+        self.poll(now).map(|_| ()).map_err(|_| PollError)
+    }
+}
@@ -0,0 +1,244 @@
+//! Time types used throughout the crate
+//!
+//! Single import surface for the time related types of the public API. Prefer the
+//! `Ui*` aliases in the application code, they keep following the types used by
+//! the crate signatures should the underlying time library change.
+//!
+//! The types are provided by the `embedded-time` crate with the `embedded-time`
+//! feature (default). Without it, a minimal built-in backend mirroring the used subset
+//! of the `embedded-time` API takes its place, so the crate doesn't pull in
+//! the dependency at all, e.g. for the projects built on another time library.
+//!
+//! # Feature unification
+//!
+//! The `embedded-time` feature is not additive, it swaps the types of the public API
+//! rather than extending it. Cargo unifies the features across the dependency graph,
+//! so a single dependent enabling it (e.g. by keeping the default features) switches
+//! the types for every other dependent as well, breaking the ones implementing the
+//! built-in [`Clock`]. Libraries depending on this crate should disable the default
+//! features and stick to the re-exports of this module, leaving the choice to the application.
+
+#[cfg(feature = "embedded-time")]
+pub use embedded_time::clock::Error as ClockError;
+#[cfg(feature = "embedded-time")]
+pub use embedded_time::duration::Milliseconds;
+#[cfg(feature = "embedded-time")]
+pub use embedded_time::fixed_point::FixedPoint;
+#[cfg(feature = "embedded-time")]
+pub use embedded_time::fraction::Fraction;
+#[cfg(feature = "embedded-time")]
+pub use embedded_time::rate::{Hertz, Rate};
+#[cfg(feature = "embedded-time")]
+pub use embedded_time::{Clock, Instant, TimeInt};
+use num_traits::{Bounded, WrappingAdd, WrappingSub};
+
+#[cfg(not(feature = "embedded-time"))]
+mod simple;
+#[cfg(not(feature = "embedded-time"))]
+pub use self::simple::{
+    Clock, ClockError, FixedPoint, Fraction, Hertz, Instant, Milliseconds, Rate, TimeInt,
+};
+
+/// Duration in the milliseconds of the native integer of the clock `C`
+pub type UiDuration<C> = Milliseconds<<C as Clock>::T>;
+
+/// Instant of the clock `C`
+pub type UiInstant<C> = Instant<C>;
+
+/// Rate in Hz of the native integer of the clock `C`
+pub type UiRate<C> = Hertz<<C as Clock>::T>;
+
+/// Returns the raw clock ticks of the instant
+#[inline]
+pub(crate) fn ticks<C: Clock>(instant: Instant<C>) -> C::T {
+    instant.duration_since_epoch().integer()
+}
+
+/// Returns the duration elapsed between `since` and `now`
+///
+/// The instants are compared with the clock wrap in mind, so the result stays
+/// correct across the clock rollover as long as the instants are less than half
+/// of the clock range apart. Saturates to the maximum duration if the elapsed
+/// ticks don't fit into milliseconds of the clock integer.
+///
+/// Returns [`None`] if `now` precedes `since`
+pub(crate) fn duration_since<C: Clock>(
+    now: Instant<C>,
+    since: Instant<C>,
+) -> Option<Milliseconds<C::T>> {
+    now.checked_duration_since(&since).map(|d| {
+        Milliseconds::<C::T>::try_from(d).unwrap_or(Milliseconds::<C::T>::new(C::T::max_value()))
+    })
+}
+
+/// Returns the duration elapsed between `since` and `now`
+///
+/// Saturates to zero if `now` precedes `since`, see [`duration_since`]
+pub(crate) fn elapsed_since<C: Clock>(now: Instant<C>, since: Instant<C>) -> Milliseconds<C::T> {
+    duration_since(now, since).unwrap_or(Milliseconds::<C::T>::new(C::T::from(0)))
+}
+
+/// Advances the `instant` by the `delta`
+///
+/// Wraps around together with the clock. The instant is left untouched if the `delta`
+/// exceeds half of the clock range, as it couldn't be told apart from a past instant.
+pub(crate) fn advance<C: Clock>(instant: Instant<C>, delta: Milliseconds<C::T>) -> Instant<C> {
+    instant.checked_add(delta).unwrap_or(instant)
+}
+
+/// Moves the `instant` back by the `delta`
+///
+/// Wraps around together with the clock. The instant is left untouched if the `delta`
+/// exceeds half of the clock range, see [`advance`].
+pub(crate) fn rewind<C: Clock>(instant: Instant<C>, delta: Milliseconds<C::T>) -> Instant<C> {
+    instant.checked_sub(delta).unwrap_or(instant)
+}
+
+/// Moves the `instant` forward by the ticks elapsed between `from` and `to`
+///
+/// The instant is left untouched if `to` precedes `from`. Wraps around
+/// together with the clock.
+pub(crate) fn shift_forward<C: Clock>(
+    instant: Instant<C>,
+    from: Instant<C>,
+    to: Instant<C>,
+) -> Instant<C> {
+    if to < from {
+        return instant;
+    }
+    let shift = ticks(to).wrapping_sub(&ticks(from));
+    Instant::<C>::new(ticks(instant).wrapping_add(&shift))
+}
+
+/// Indicates that the `instant` lies after `from` and no later than `to`
+///
+/// The instants are measured forward from `from`, so unlike [`duration_since`]
+/// the result holds for the spans of up to the whole clock range.
+pub(crate) fn is_within<C: Clock>(instant: Instant<C>, from: Instant<C>, to: Instant<C>) -> bool {
+    let offset = ticks(instant).wrapping_sub(&ticks(from));
+    offset > C::T::from(0) && offset <= ticks(to).wrapping_sub(&ticks(from))
+}
+
+/// Moves the `anchor` of a repeating sequence forward by the whole `period`s elapsed
+/// until `now`
+///
+/// Returns the duration elapsed since the moved anchor, shorter than the `period`.
+/// Keeps the anchor within a single period of `now`, so the elapsed duration doesn't
+/// saturate once the sequence has been running for over half of the clock range.
+/// Zero `period` leaves the anchor untouched.
+pub(crate) fn realign<C: Clock>(
+    anchor: &mut Instant<C>,
+    now: Instant<C>,
+    period: Milliseconds<C::T>,
+) -> Milliseconds<C::T> {
+    let (elapsed, period) = (elapsed_since(now, *anchor).integer(), period.integer());
+    if period == C::T::from(0) || elapsed < period {
+        return Milliseconds::<C::T>::new(elapsed);
+    }
+    let rest = elapsed % period;
+    *anchor = advance(*anchor, Milliseconds::<C::T>::new(elapsed - rest));
+    Milliseconds::<C::T>::new(rest)
+}
+
+/// Indicates that the `elapsed` duration has run past the `limit`
+///
+/// Compares the raw integers of the same clock, so no scaling conversion between
+/// the duration types takes place
+#[inline]
+pub(crate) fn is_past<T: TimeInt>(elapsed: Milliseconds<T>, limit: Milliseconds<T>) -> bool {
+    elapsed.integer() > limit.integer()
+}
+
+/// Indicates that the `elapsed` duration has reached the `limit`
+///
+/// See [`is_past`]
+#[inline]
+pub(crate) fn has_reached<T: TimeInt>(elapsed: Milliseconds<T>, limit: Milliseconds<T>) -> bool {
+    elapsed.integer() >= limit.integer()
+}
+
+/// Returns the part of the `limit` left after the `elapsed` duration
+///
+/// Saturates to zero once the `elapsed` duration reaches the `limit`
+#[inline]
+pub(crate) fn remaining<T: TimeInt>(
+    elapsed: Milliseconds<T>,
+    limit: Milliseconds<T>,
+) -> Milliseconds<T> {
+    match has_reached(elapsed, limit) {
+        true => Milliseconds::<T>::new(T::from(0)),
+        false => Milliseconds::<T>::new(limit.integer() - elapsed.integer()),
+    }
+}
+
+/// Converts the clock integer into [`u32`], saturating at [`u32::MAX`]
+///
+/// The single conversion of the clock integers into the plain integers, e.g. for
+/// the levels, the phases and the cycle counts derived from the durations.
+pub(crate) fn saturating_u32<T: TimeInt>(value: T) -> u32 {
+    if value >= T::from(u32::MAX) {
+        return u32::MAX;
+    }
+    (0..u32::BITS).rev().fold(0u32, |result, bit| {
+        let next = result | 1 << bit;
+        if T::from(next) <= value {
+            next
+        } else {
+            result
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockClock;
+
+    #[test]
+    fn saturating_u32_at_the_boundaries() {
+        assert_eq!(saturating_u32(0u32), 0);
+        assert_eq!(saturating_u32(u32::MAX), u32::MAX);
+        assert_eq!(saturating_u32(0u64), 0);
+        assert_eq!(saturating_u32(u64::from(u32::MAX) - 1), u32::MAX - 1);
+        assert_eq!(saturating_u32(u64::from(u32::MAX) + 1), u32::MAX);
+        assert_eq!(saturating_u32(u64::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn duration_comparisons_at_the_boundaries() {
+        let (zero, max) = (Milliseconds(0u32), Milliseconds(u32::MAX));
+
+        assert!(!is_past(zero, zero));
+        assert!(has_reached(zero, zero));
+        assert_eq!(remaining(zero, zero), zero);
+
+        assert!(!is_past(max, max));
+        assert!(has_reached(max, max));
+        assert!(is_past(max, Milliseconds(u32::MAX - 1)));
+        assert!(!has_reached(Milliseconds(u32::MAX - 1), max));
+        assert_eq!(remaining(zero, max), max);
+        assert_eq!(remaining(max, zero), zero);
+    }
+
+    #[test]
+    fn elapsed_just_past_the_clock_wrap() {
+        let before = Instant::<MockClock>::new(u32::MAX - 4);
+        let after = Instant::<MockClock>::new(5);
+
+        assert_eq!(duration_since(after, before), Some(Milliseconds(10)));
+        assert_eq!(duration_since(before, after), None);
+        assert_eq!(elapsed_since(before, after), Milliseconds(0u32));
+        assert_eq!(advance(before, Milliseconds(10)), after);
+        assert_eq!(rewind(after, Milliseconds(10)), before);
+    }
+
+    #[test]
+    fn realign_keeps_the_anchor_within_a_period() {
+        let mut anchor = Instant::<MockClock>::new(u32::MAX - 4);
+        let now = Instant::<MockClock>::new(2_005);
+
+        let elapsed = realign(&mut anchor, now, Milliseconds(1_000));
+        assert_eq!(elapsed, Milliseconds(10u32));
+        assert_eq!(anchor, Instant::<MockClock>::new(1_995));
+    }
+}
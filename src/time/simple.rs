@@ -0,0 +1,339 @@
+//! Minimal time backend used without the `embedded-time` feature
+//!
+//! Mirrors the subset of the `embedded-time` API the crate is built on, so that
+//! the UI elements don't depend on the backend. The clocks count the ticks of
+//! a [`TimeInt`] scaled to seconds by their [`Clock::SCALING_FACTOR`], the
+//! durations are plain millisecond newtypes.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::Hash;
+use core::ops;
+use num_traits::{
+    Bounded, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Num, WrappingAdd, WrappingSub,
+};
+
+/// Milliseconds in a second, the scale of the [`Milliseconds`]
+const MS_PER_SECOND: u64 = 1_000;
+
+/// Unsigned integer type of the clock ticks and the durations
+pub trait TimeInt:
+    Copy
+    + Ord
+    + Num
+    + Bounded
+    + WrappingAdd
+    + WrappingSub
+    + CheckedAdd
+    + CheckedSub
+    + CheckedMul
+    + CheckedDiv
+    + From<u32>
+    + Hash
+    + fmt::Debug
+    + fmt::Display
+{
+}
+
+impl TimeInt for u32 {}
+impl TimeInt for u64 {}
+
+/// Fraction scaling the clock ticks to seconds, see [`Clock::SCALING_FACTOR`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fraction {
+    numerator: u32,
+    denominator: u32,
+}
+
+impl Fraction {
+    /// Create new [`Fraction`] of `numerator` / `denominator`
+    pub const fn new(numerator: u32, denominator: u32) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Returns the numerator of the fraction
+    pub const fn numerator(&self) -> &u32 {
+        &self.numerator
+    }
+
+    /// Returns the denominator of the fraction
+    pub const fn denominator(&self) -> &u32 {
+        &self.denominator
+    }
+}
+
+/// Error reported by the [`Clock::try_now`]
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ClockError {
+    /// Exact cause of failure is unknown
+    #[default]
+    Unspecified,
+    /// The clock has either stopped or never started
+    NotRunning,
+}
+
+/// Error of the conversions between the time types
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConversionError {
+    /// Exact cause of failure is unknown
+    Unspecified,
+    /// The result doesn't fit into the integer type
+    Overflow,
+    /// Attempted to divide by zero, e.g. the period of a zero rate
+    DivByZero,
+}
+
+/// Source of the current [`Instant`]
+pub trait Clock: Sized {
+    /// Integer type of the clock ticks
+    type T: TimeInt;
+
+    /// Duration of a single tick in seconds, e.g. `Fraction::new(1, 1_000)`
+    /// for a millisecond clock
+    const SCALING_FACTOR: Fraction;
+
+    /// Returns the current instant of the clock
+    fn try_now(&self) -> Result<Instant<Self>, ClockError>;
+}
+
+/// Value backed by an integer, e.g. the [`Milliseconds`]
+pub trait FixedPoint: Sized + Copy {
+    /// Integer type of the value
+    type T: TimeInt;
+
+    /// Returns the integer of the value
+    fn integer(&self) -> Self::T;
+}
+
+/// Duration in milliseconds
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Milliseconds<T: TimeInt = u32>(pub T);
+
+impl<T: TimeInt> Milliseconds<T> {
+    /// Create new [`Milliseconds`] of the `value`
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: TimeInt> FixedPoint for Milliseconds<T> {
+    type T = T;
+
+    fn integer(&self) -> T {
+        self.0
+    }
+}
+
+impl<T: TimeInt> ops::Add for Milliseconds<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl<T: TimeInt> ops::Sub for Milliseconds<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+/// Duration in the ticks of a clock, as returned by the [`Instant`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Generic<T: TimeInt> {
+    ticks: T,
+    scaling_factor: Fraction,
+}
+
+impl<T: TimeInt> FixedPoint for Generic<T> {
+    type T = T;
+
+    fn integer(&self) -> T {
+        self.ticks
+    }
+}
+
+impl<T: TimeInt> TryFrom<Generic<T>> for Milliseconds<T> {
+    type Error = ConversionError;
+
+    fn try_from(duration: Generic<T>) -> Result<Self, Self::Error> {
+        let Fraction {
+            numerator,
+            denominator,
+        } = duration.scaling_factor;
+        let (numerator, denominator) =
+            reduce(u64::from(numerator) * MS_PER_SECOND, u64::from(denominator))?;
+        scale(duration.ticks, numerator, denominator).map(Self)
+    }
+}
+
+/// Rate in Hz
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hertz<T: TimeInt = u32>(pub T);
+
+impl<T: TimeInt> Hertz<T> {
+    /// Create new [`Hertz`] of the `value`
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: TimeInt> FixedPoint for Hertz<T> {
+    type T = T;
+
+    fn integer(&self) -> T {
+        self.0
+    }
+}
+
+/// Rate convertible into the duration of its period
+pub trait Rate: FixedPoint {
+    /// Returns the period of the rate, rounded down to whole milliseconds
+    ///
+    /// Returns an error for a zero rate
+    fn to_duration<D: From<Milliseconds<Self::T>>>(&self) -> Result<D, ConversionError>;
+}
+
+impl<T: TimeInt> Rate for Hertz<T> {
+    fn to_duration<D: From<Milliseconds<T>>>(&self) -> Result<D, ConversionError> {
+        let ms_per_second = T::from(MS_PER_SECOND as u32);
+        let period = ms_per_second
+            .checked_div(&self.0)
+            .ok_or(ConversionError::DivByZero)?;
+        Ok(D::from(Milliseconds(period)))
+    }
+}
+
+/// Instant of the clock `C`, the number of ticks since the clock epoch
+///
+/// The instants are ordered with the clock rollover in mind, an instant less than
+/// half of the clock range ahead of the other is considered later than it.
+#[derive(Debug)]
+pub struct Instant<C: Clock> {
+    ticks: C::T,
+}
+
+impl<C: Clock> Instant<C> {
+    /// Create new [`Instant`] at the `ticks` since the clock epoch
+    pub fn new(ticks: C::T) -> Self {
+        Self { ticks }
+    }
+
+    /// Returns the duration elapsed since the `other` instant
+    ///
+    /// Returns [`None`] if the `other` instant is later than this one
+    pub fn checked_duration_since(&self, other: &Self) -> Option<Generic<C::T>> {
+        (self >= other).then(|| Generic {
+            ticks: self.ticks.wrapping_sub(&other.ticks),
+            scaling_factor: C::SCALING_FACTOR,
+        })
+    }
+
+    /// Returns the duration elapsed since the clock epoch
+    pub fn duration_since_epoch(&self) -> Generic<C::T> {
+        Generic {
+            ticks: self.ticks,
+            scaling_factor: C::SCALING_FACTOR,
+        }
+    }
+
+    /// Returns the instant the `duration` after this one
+    ///
+    /// Returns [`None`] if the `duration` exceeds half of the clock range
+    pub fn checked_add(self, duration: Milliseconds<C::T>) -> Option<Self> {
+        let ticks = Self::to_ticks(duration)?;
+        Some(Self::new(self.ticks.wrapping_add(&ticks)))
+    }
+
+    /// Returns the instant the `duration` before this one
+    ///
+    /// Returns [`None`] if the `duration` exceeds half of the clock range
+    pub fn checked_sub(self, duration: Milliseconds<C::T>) -> Option<Self> {
+        let ticks = Self::to_ticks(duration)?;
+        Some(Self::new(self.ticks.wrapping_sub(&ticks)))
+    }
+
+    /// Converts the `duration` into the clock ticks, up to half of the clock range
+    fn to_ticks(duration: Milliseconds<C::T>) -> Option<C::T> {
+        let Fraction {
+            numerator,
+            denominator,
+        } = C::SCALING_FACTOR;
+        let (numerator, denominator) =
+            reduce(u64::from(denominator), u64::from(numerator) * MS_PER_SECOND).ok()?;
+        let ticks = scale(duration.0, numerator, denominator).ok()?;
+        (ticks <= C::T::max_value() / C::T::from(2)).then_some(ticks)
+    }
+}
+
+impl<C: Clock> Clone for Instant<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Clock> Copy for Instant<C> {}
+
+impl<C: Clock> PartialEq for Instant<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ticks == other.ticks
+    }
+}
+
+impl<C: Clock> Eq for Instant<C> {}
+
+impl<C: Clock> PartialOrd for Instant<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Clock> Ord for Instant<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.ticks == other.ticks {
+            return Ordering::Equal;
+        }
+        // the later instant is less than half of the clock range ahead
+        self.ticks
+            .wrapping_sub(&other.ticks)
+            .cmp(&(C::T::max_value() / C::T::from(2)))
+            .reverse()
+    }
+}
+
+impl<C: Clock> Hash for Instant<C> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.ticks.hash(state);
+    }
+}
+
+/// Reduces the fraction, so that both of its parts fit into [`u32`]
+fn reduce(numerator: u64, denominator: u64) -> Result<(u32, u32), ConversionError> {
+    if denominator == 0 {
+        return Err(ConversionError::DivByZero);
+    }
+    let (mut a, mut b) = (numerator, denominator);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    let gcd = a.max(1);
+    let numerator = u32::try_from(numerator / gcd).map_err(|_| ConversionError::Overflow)?;
+    let denominator = u32::try_from(denominator / gcd).map_err(|_| ConversionError::Overflow)?;
+    Ok((numerator, denominator))
+}
+
+/// Multiplies the `value` by the `numerator` / `denominator` fraction
+fn scale<T: TimeInt>(value: T, numerator: u32, denominator: u32) -> Result<T, ConversionError> {
+    value
+        .checked_mul(&T::from(numerator))
+        .ok_or(ConversionError::Overflow)?
+        .checked_div(&T::from(denominator))
+        .ok_or(ConversionError::DivByZero)
+}